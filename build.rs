@@ -1,14 +1,37 @@
 use serde_json::Value;
-use std::{env, fs::write};
+use std::{
+    collections::BTreeSet,
+    env,
+    fs::{read_dir, read_to_string, write},
+};
 
 fn main() {
-    let json_schema = serde_json::from_str(include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/schema.json"
-    )))
-    .expect("Failed to deserialize RPC Schema");
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/schemas");
+    let mut schemas = read_dir(dir)
+        .expect("Failed to read the schemas directory")
+        .map(|entry| entry.expect("Failed to read the schemas directory").path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .map(|path| {
+            let file = path.file_name().unwrap().to_string_lossy().to_string();
+            let json_schema = serde_json::from_str(&read_to_string(&path).unwrap())
+                .unwrap_or_else(|e| panic!("Failed to deserialize RPC Schema {file}: {e}"));
+            VersionedSchema::new(file.clone(), json_schema)
+                .unwrap_or_else(|| panic!("RPC Schema {file} is missing info.version"))
+        })
+        .collect::<Vec<VersionedSchema>>();
+    assert!(!schemas.is_empty(), "No RPC Schemas found in {dir}");
+
+    // oldest first, so the last one is the latest
+    schemas.sort_by_key(|s| s.sort_key());
+    for pair in schemas.windows(2) {
+        assert_ne!(
+            pair[0].module, pair[1].module,
+            "{} and {} have the same version",
+            pair[0].file, pair[1].file
+        );
+    }
 
-    let code = generate(&json_schema).expect("Failed to generate json rpc bindings");
+    let code = generate(&schemas).expect("Failed to generate json rpc bindings");
 
     write(
         format!("{}/json_rpc_bindings.rs", env::var("OUT_DIR").unwrap()),
@@ -18,40 +41,319 @@ fn main() {
 }
 
 const CURLY: [char; 2] = ['{', '}'];
-const IDENTATION: &'static str = "    ";
-const FN_IDENTATION: &'static str = "        ";
-const DEFAULT_DERIVES: &'static str =
+const IDENTATION: &str = "    ";
+const FN_IDENTATION: &str = "        ";
+const DEFAULT_DERIVES: &str =
     "#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]";
+const OPTIONAL_DERIVES: &str =
+    "#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash, Default)]";
+
+/// Properties the server always sends, even though the schema doesn't mark them as `required`.
+///
+/// Every other property is generated as an `Option<T>`, since the server is free to omit them.
+const ALWAYS_PRESENT: &[(&str, &str)] = &[
+    ("ip_ban", "ip"),
+    ("kick_player", "player"),
+    ("operator", "player"),
+    ("server_state", "started"),
+    ("system_message", "message"),
+    ("typed_game_rule", "key"),
+    ("typed_game_rule", "type"),
+    ("typed_game_rule", "value"),
+    ("untyped_game_rule", "key"),
+    ("untyped_game_rule", "value"),
+    ("user_ban", "player"),
+    ("version", "name"),
+    ("version", "protocol"),
+];
+
+/// Fields with a hand-written type instead of the one in the schema, which only says `string`.
+const FIELD_TYPES: &[(&str, &str, &str)] = &[
+    ("incoming_ip_ban", "expires", "crate::expiry::Expiry"),
+    ("ip_ban", "expires", "crate::expiry::Expiry"),
+    ("player", "id", "crate::player::PlayerId"),
+    ("user_ban", "expires", "crate::expiry::Expiry"),
+];
+
+/// Types with a hand-written `crate::check::Check`, checked before they are sent in a request.
+/// Every type containing one of them is checked as well.
+const CHECKED: &[&str] = &["message", "player"];
+
+/// A schema from the `schemas` directory, generated into its own module.
+struct VersionedSchema {
+    /// The file name within `schemas`.
+    file: String,
+    /// The protocol version, `info.version` of the schema.
+    version: String,
+    /// The module name, e.g. `v2_0_0`.
+    module: String,
+    schema: Value,
+}
+
+impl VersionedSchema {
+    fn new(file: String, schema: Value) -> Option<Self> {
+        let version = schema.get("info")?.get("version")?.as_str()?.to_string();
+        let module = format!(
+            "v{}",
+            version.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        );
+
+        Some(Self {
+            file,
+            version,
+            module,
+            schema,
+        })
+    }
+
+    /// Orders versions numerically, so `2.10.0` comes after `2.9.0`.
+    fn sort_key(&self) -> Vec<u64> {
+        self.version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    }
+}
 
-pub fn generate(schema: &Value) -> Option<String> {
+/// The code of a single schema component, a struct or enum along with any enums generated for its fields.
+struct TypeUnit {
+    /// Every type name the code defines.
+    defines: Vec<String>,
+    code: String,
+}
+
+fn generate(schemas: &[VersionedSchema]) -> Option<String> {
     let mut code = String::new();
 
-    code.push_str(dependencies());
-    code.push('\n');
+    let types = schemas
+        .iter()
+        .map(|s| type_units(&s.schema))
+        .collect::<Option<Vec<Vec<TypeUnit>>>>()?;
+    let shared = shared_types(&types);
+
+    // types that are identical in every version are only generated once, at the crate root
+    code.push_str("\nuse serde::{Deserialize, Serialize};\n");
+    for unit in types.last()? {
+        if shared.contains(&unit.defines[0]) {
+            code.push_str(&unit.code);
+        }
+    }
 
-    code.push_str(&base_client());
-    code.push('\n');
+    for (schema, types) in schemas.iter().zip(types) {
+        code.push_str(&format!(
+            "\n/// Bindings for version `{}` of the protocol, generated from `schemas/{}`.\npub mod {} {}\n",
+            schema.version, schema.file, schema.module, CURLY[0]
+        ));
+        code.push_str(&version_code(schema, types, &shared)?);
+        code.push_str(&format!("{}\n", CURLY[1]));
+    }
+
+    let latest = schemas.last()?;
+    code.push_str(&format!(
+        "\n/// The latest protocol version, `{}`, is also available at the crate root.\npub use {}::*;\n",
+        latest.version, latest.module
+    ));
+
+    Some(code)
+}
+
+fn type_units(schema: &Value) -> Option<Vec<TypeUnit>> {
+    let mut units = vec![];
+    let checked = checked_types(schema)?;
 
     println!("schemas");
     for (parent_key, schema) in schema.get("components")?.get("schemas")?.as_object()? {
         // its either a struct or an enum so we just check both
-        let schema_code = if let Some(c) = EnumData::from_value(&parent_key, &schema) {
-            c.to_code()
-        } else if let Some(c) = StructData::from_value(&parent_key, &schema) {
-            c.to_code()
+        let code = if let Some(c) = EnumData::from_value(parent_key, schema) {
+            c.into_code()
+        } else if let Some(c) = StructData::from_value(parent_key, schema) {
+            c.into_code(&checked)
         } else {
             return None;
         };
 
-        code.push_str(&schema_code);
+        let defines = code
+            .lines()
+            .filter_map(|line| {
+                line.strip_prefix("pub struct ")
+                    .or_else(|| line.strip_prefix("pub enum "))
+            })
+            .filter_map(|rest| rest.split([' ', '{']).next())
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+        if defines.is_empty() {
+            return None;
+        }
+
+        units.push(TypeUnit { defines, code });
+    }
+
+    Some(units)
+}
+
+/// Returns the names of every [`CHECKED`] type and every struct containing one, directly or through other structs.
+fn checked_types(schema: &Value) -> Option<BTreeSet<String>> {
+    let structs = schema
+        .get("components")?
+        .get("schemas")?
+        .as_object()?
+        .iter()
+        .filter(|(parent_key, schema)| EnumData::from_value(parent_key, schema).is_none())
+        .filter_map(|(parent_key, schema)| StructData::from_value(parent_key, schema))
+        .collect::<Vec<StructData>>();
+
+    let mut checked = CHECKED
+        .iter()
+        .map(|name| to_pascal_case(name))
+        .collect::<BTreeSet<String>>();
+    loop {
+        let before = checked.len();
+        for data in &structs {
+            if data
+                .fields
+                .iter()
+                .any(|f| checked.contains(f.rust_type.item()))
+            {
+                checked.insert(to_pascal_case(&data.name));
+            }
+        }
+
+        if checked.len() == before {
+            return Some(checked);
+        }
     }
+}
+
+/// Returns the names of every type that is generated the same in every version.
+///
+/// A type is only shared if every type it refers to is shared as well,
+/// otherwise the same code could mean different types in different versions.
+fn shared_types(types: &[Vec<TypeUnit>]) -> BTreeSet<String> {
+    let Some((first, rest)) = types.split_first() else {
+        return BTreeSet::new();
+    };
+    let all = types
+        .iter()
+        .flatten()
+        .flat_map(|unit| unit.defines.iter().cloned())
+        .collect::<BTreeSet<String>>();
+
+    let mut shared = first
+        .iter()
+        .filter(|unit| {
+            rest.iter()
+                .all(|units| units.iter().any(|other| other.code == unit.code))
+        })
+        .collect::<Vec<&TypeUnit>>();
 
-    // wrap all methods inside the base client
-    code.push_str(&format!("impl Client {}\n", CURLY[0]));
-    for method in schema.get("methods")?.as_array()? {
-        code.push_str(&FunctionData::from_value(&method)?.to_code());
+    loop {
+        let names = shared
+            .iter()
+            .flat_map(|unit| unit.defines.iter().cloned())
+            .collect::<BTreeSet<String>>();
+        let before = shared.len();
+        shared.retain(|unit| {
+            unit.code
+                .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .filter(|word| all.contains(*word))
+                .all(|word| names.contains(word))
+        });
+
+        if shared.len() == before {
+            return names;
+        }
     }
-    code.push_str(&format!("\n{}", CURLY[1]));
+}
+
+fn version_code(
+    schema: &VersionedSchema,
+    types: Vec<TypeUnit>,
+    shared: &BTreeSet<String>,
+) -> Option<String> {
+    let mut code = String::new();
+    let schema_file = &schema.file;
+    let version = &schema.version;
+    let schema = &schema.schema;
+
+    code.push_str(dependencies());
+    code.push('\n');
+    if !shared.is_empty() {
+        code.push_str(&format!(
+            "pub use crate::{}{}{};\n",
+            CURLY[0],
+            shared.iter().cloned().collect::<Vec<String>>().join(", "),
+            CURLY[1]
+        ));
+    }
+    code.push_str(&format!(
+        r#"
+/// The protocol version these bindings were generated for, as reported by `rpc.discover`.
+pub const VERSION: &str = "{version}";
+/// The schema these bindings were generated from, as returned by `rpc.discover`.
+pub const SCHEMA: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/schemas/{schema_file}"));
+"#
+    ));
+
+    code.push_str(&base_client());
+    code.push('\n');
+
+    let types = types
+        .into_iter()
+        .filter(|unit| !shared.contains(&unit.defines[0]))
+        .collect::<Vec<TypeUnit>>();
+    if !types.is_empty() {
+        code.push_str("use serde::{Deserialize, Serialize};\n");
+    }
+    for unit in types {
+        code.push_str(&unit.code);
+    }
+
+    let mut functions = schema
+        .get("methods")?
+        .as_array()?
+        .iter()
+        .map(FunctionData::from_value)
+        .collect::<Option<Vec<FunctionData>>>()?;
+    let checked = checked_types(schema)?;
+    for param in functions.iter_mut().flat_map(|f| &mut f.params) {
+        param.checked = checked.contains(param.rust_type.item());
+    }
+
+    // the server half, a trait with every request and a dispatcher calling into it
+    code.push_str(&handler_code(&functions));
+
+    // and a batch with every request
+    code.push_str(&batch_code(&functions));
+
+    // and a struct with every server setting
+    code.push_str(&settings_code(&functions));
+
+    // and a REST gateway with a route per request
+    code.push_str(&gateway_code(&functions));
+
+    // and every request on a fleet of servers at once
+    code.push_str(&fleet_code(&functions));
+
+    // group all methods by their endpoint path into sub-clients of the base client
+    let mut root = ClientGroup::default();
+    for function in functions {
+        root.insert(&function.path.clone(), function);
+    }
+    code.push_str(&root.into_code());
+
+    // and a single enum for every notification
+    let events = schema
+        .get("methods")?
+        .as_array()?
+        .iter()
+        .filter(|m| {
+            m.get("name")
+                .and_then(|n| n.as_str())
+                .is_some_and(|n| n.starts_with("minecraft:notification/"))
+        })
+        .map(EventVariant::from_value)
+        .collect::<Option<Vec<EventVariant>>>()?;
+    code.push_str(&events_code(events));
 
     Some(code)
 }
@@ -59,9 +361,32 @@ pub fn generate(schema: &Value) -> Option<String> {
 fn dependencies() -> &'static str {
     r#"
 use std::{result::Result as StdResult, time::Duration};
-use serde::{Deserialize, Serialize};
 use futures_util::TryStreamExt as _;
 use tokio_stream::{Stream, wrappers::{BroadcastStream, errors::BroadcastStreamRecvError}};
+use crate::notification::{Notifications, decode_stream};
+use crate::request::Requests;
+pub use crate::notification::NotificationError;
+pub use crate::dynamic::{DynamicClient, DynamicError};
+pub use crate::compat::{ChangedMethod, CompatibilityReport, Negotiation, NegotiationError};
+use crate::handler::{handle_message, parse_request, request_param, response, to_result};
+use crate::batch::BatchRequests;
+pub use crate::batch::{BatchEntry, BatchError, BatchResults};
+pub use crate::message::MessageError;
+pub use crate::expiry::{Expiry, ParseExpiryError};
+pub use crate::player::{InvalidPlayerId, PlayerId};
+pub use crate::reconcile::{AllowlistPlan, ListEntry, ListPlan};
+pub use crate::properties::PropertiesError;
+pub use crate::settings::{SettingChange, SettingEndpoints};
+pub use crate::spec::{GameRuleChange, ServerPlan, ServerSpec};
+pub use crate::fleet::{Fleet, FleetResults, FleetSelection};
+use crate::fleet::merge;
+pub use crate::handler::{HandlerError, MessageHandler, Notification, Notifier};
+#[cfg(feature = "server")]
+pub use crate::handler::serve;
+#[cfg(feature = "gateway")]
+pub use crate::gateway::GatewayRoute;
+#[cfg(feature = "gateway")]
+use crate::gateway::{gateway_events, gateway_response};
 pub use pale::{ClientConfig, Result, PaleError, RPCError, StreamExt, WebSocketConfig};"#
 }
 
@@ -70,11 +395,100 @@ fn base_client() -> String {
     format!(
         r#"
 #[derive(Debug, Clone)]
-pub struct Client(pub(crate) pale::Client);
+pub struct Client(
+    pub(crate) pale::Client,
+    /// Shared by every clone so all notification streams use the same subscriptions.
+    pub(crate) Notifications,
+    /// Shared by every clone so requests on the connection are sent one at a time.
+    pub(crate) Requests,
+);
 
 impl Client {0}
     pub async fn new(uri: impl AsRef<str>, config: ClientConfig) -> Result<Self> {0}
-        Ok(Self(pale::Client::new(uri, config).await?))
+        Ok(Self(pale::Client::new(uri, config).await?, Notifications::default(), Requests::default()))
+    {1}
+
+    /// Connects like [`Client::new`], then compares the schema the server reports from `rpc.discover` with the compiled [`SCHEMA`].
+    ///
+    /// In [`Negotiation::Strict`] mode an incompatible server is disconnected again and returned as [`NegotiationError::Incompatible`].
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use mc_rpc::{0}Client, ClientConfig, Negotiation{1};
+    /// # async fn example() -> Result<(), mc_rpc::NegotiationError> {0}
+    /// let (client, report) = Client::negotiate("ws://localhost:25585", ClientConfig::default(), Negotiation::Lenient).await?;
+    /// for method in &report.missing_methods {0}
+    ///     println!("The server doesn't support {0}method{1}");
+    /// {1}
+    /// # Ok(())
+    /// # {1}
+    /// ```
+    pub async fn negotiate(
+        uri: impl AsRef<str>,
+        config: ClientConfig,
+        negotiation: Negotiation,
+    ) -> StdResult<(Self, CompatibilityReport), NegotiationError> {0}
+        let client = Self::new(uri, config).await?;
+        let report = match client.compatibility().await {0}
+            Ok(report) => report,
+            Err(e) => {0}
+                let _ = client.close().await;
+                return Err(e.into());
+            {1}
+        {1};
+
+        if negotiation == Negotiation::Strict && !report.is_compatible() {0}
+            // failing to close doesn't change why the server was refused
+            let _ = client.close().await;
+            return Err(NegotiationError::Incompatible(Box::new(report)));
+        {1}
+
+        Ok((client, report))
+    {1}
+
+    /// Calls `rpc.discover` and compares the server's schema with the compiled [`SCHEMA`].
+    pub async fn compatibility(&self) -> Result<CompatibilityReport> {0}
+        let server: serde_json::Value = self.request("rpc.discover", None).await?;
+        let compiled: serde_json::Value = serde_json::from_str(SCHEMA)?;
+        Ok(CompatibilityReport::new(&compiled, &server))
+    {1}
+
+    /// Returns a clone of this client whose requests give up after `timeout`, instead of [`ClientConfig::request_timeout`].
+    ///
+    /// The clone shares the connection and notification subscriptions of this client.
+    /// Requests on a connection are sent one at a time, so the time spent waiting for earlier ones counts towards `timeout` as well.
+    /// Once a request gives up, or its future is dropped, the next one is sent, a server that never answers only holds up the others for `timeout`.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # async fn example(client: mc_rpc::Client) -> mc_rpc::Result<()> {0}
+    /// let players = client.with_timeout(Duration::from_secs(2)).players().get().await?;
+    /// # Ok(())
+    /// # {1}
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> Self {0}
+        Self(self.0.clone(), self.1.clone(), self.2.with_timeout(timeout))
+    {1}
+
+    /// Returns a [`DynamicClient`] on this connection for the methods described by `schema`, an OpenRPC document.
+    pub fn dynamic(&self, schema: serde_json::Value) -> DynamicClient {0}
+        DynamicClient::new(self.0.clone(), self.1.clone(), self.2.clone(), schema)
+    {1}
+
+    /// Returns a [`DynamicClient`] on this connection for every method the server reports from `rpc.discover`.
+    pub async fn discover(&self) -> Result<DynamicClient> {0}
+        let schema = self.request("rpc.discover", None).await?;
+        Ok(self.dynamic(schema))
+    {1}
+
+    /// Sends a request through the shared [`Requests`] of this connection.
+    async fn request<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<std::collections::HashMap<String, serde_json::Value>>,
+    ) -> Result<T> {0}
+        self.2.send(&self.0, method, params).await
     {1}
 
     /// Calling [`Self::close`] means:
@@ -85,6 +499,7 @@ impl Client {0}
     /// The [`Client`] is not guaranteed to be 100% closed after this function returns.
     /// It may take a little while, use [`Self::wait_for_connection`] to make sure before, let's say reconnecting.
     pub async fn close(&self) -> Result<()> {0}
+        self.2.batches().close().await;
         self.0.close().await
     {1}
 
@@ -99,24 +514,30 @@ impl Client {0}
     ///
     /// ## Example
     /// ```no_run
+    /// # use std::time::Duration;
+    /// # async fn example(client: mc_rpc::Client) -> mc_rpc::Result<()> {0}
     /// // waits for the underlying connection to be ready & connected
-    /// client.wait_for_connection(true, Duration::from_secs(5)).await;
+    /// client.wait_for_connection(true, Duration::from_secs(5)).await?;
     ///
     /// // waits for the connection to disconnect
-    /// client.wait_for_connection(false, Duration::from_secs(5)).await;
+    /// client.wait_for_connection(false, Duration::from_secs(5)).await?;
+    /// # Ok(())
+    /// # {1}
     /// ```
     pub async fn wait_for_connection(&self, state: bool, timeout_duration: Duration) -> Result<()> {0}
         self.0.wait_for_connection(state, timeout_duration).await
     {1}
 
     /// Returns a [`Stream`] where a message of type [`Client`] will be sent upon each successful reconnection.
-    pub fn on_reconnect(&self) -> impl Stream<Item = StdResult<Self, BroadcastStreamRecvError>> {0}
-        BroadcastStream::new(self.0.on_reconnect()).map_ok(Self)
+    pub fn on_reconnect(&self) -> impl Stream<Item = StdResult<Self, BroadcastStreamRecvError>> + use<> {0}
+        let (notifications, requests) = (self.1.clone(), self.2.clone());
+        BroadcastStream::new(self.0.on_reconnect()).map_ok(move |client| Self(client, notifications.clone(), requests.clone()))
     {1}
 
     /// Returns a [`Stream`] where a message of type [`Client`] will be sent upon disconnect.
-    pub fn on_disconnect(&self) -> impl Stream<Item = StdResult<Self, BroadcastStreamRecvError>> {0}
-        BroadcastStream::new(self.0.on_disconnect()).map_ok(Self)
+    pub fn on_disconnect(&self) -> impl Stream<Item = StdResult<Self, BroadcastStreamRecvError>> + use<> {0}
+        let (notifications, requests) = (self.1.clone(), self.2.clone());
+        BroadcastStream::new(self.0.on_disconnect()).map_ok(move |client| Self(client, notifications.clone(), requests.clone()))
     {1}
 
 {1}"#,
@@ -145,7 +566,7 @@ impl RustType {
                 let vec_type = if let Some(item_type) = items.get("type") {
                     item_type.as_str()?
                 } else if let Some(item_ref) = items.get("$ref") {
-                    item_ref.as_str()?.split('/').last()?
+                    item_ref.as_str()?.split('/').next_back()?
                 } else {
                     return None;
                 };
@@ -154,7 +575,7 @@ impl RustType {
                 schema_type_to_rust(_type)
             }
         } else if let Some(_ref) = type_data.get("$ref") {
-            schema_type_to_rust(_ref.as_str()?.split('/').last()?)
+            schema_type_to_rust(_ref.as_str()?.split('/').next_back()?)
         } else {
             return None;
         };
@@ -169,6 +590,19 @@ impl RustType {
     fn inner(&self) -> &str {
         &self.0
     }
+
+    /// The type of the items if this is a `Vec`, otherwise the type itself.
+    fn item(&self) -> &str {
+        self.0
+            .strip_prefix("Vec<")
+            .and_then(|item| item.strip_suffix('>'))
+            .unwrap_or(&self.0)
+    }
+
+    /// Whether this is one of the `Copy` primitives of [`schema_type_to_rust`].
+    fn is_copy(&self) -> bool {
+        matches!(self.0.as_str(), "i32" | "bool")
+    }
 }
 
 /// Returns a bool that indicates if the string was modified & as well as making the text snake_case
@@ -223,11 +657,22 @@ struct StructData {
 
 impl StructData {
     fn from_value(parent_key: &str, data: &Value) -> Option<Self> {
+        // the schema doesn't use `required` yet but we respect it if it ever shows up
+        let required = data
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|s| s.as_str()).collect::<Vec<&str>>())
+            .unwrap_or_default();
+
         let fields = data
             .get("properties")?
             .as_object()?
             .iter()
-            .map(|(name, data)| Field::from_value(&parent_key, &name, data))
+            .map(|(name, data)| {
+                let is_required = required.contains(&name.as_str())
+                    || ALWAYS_PRESENT.contains(&(parent_key, name.as_str()));
+                Field::from_value(parent_key, name, data, is_required)
+            })
             .collect::<Option<Vec<Field>>>()?;
 
         Some(StructData {
@@ -236,22 +681,27 @@ impl StructData {
         })
     }
 
-    fn to_code(self) -> String {
+    /// `checked` are the types from [`checked_types`].
+    fn into_code(self, checked: &BTreeSet<String>) -> String {
         let mut code = String::new();
+        let name = to_pascal_case(&self.name);
 
-        code.push_str(&format!("{DEFAULT_DERIVES}\n"));
-        code.push_str(&format!(
-            "pub struct {} {}\n",
-            to_pascal_case(&self.name),
-            CURLY[0]
-        ));
+        // structs with only optional fields can be built with `..Default::default()`
+        if self.fields.iter().all(|f| !f.required) {
+            code.push_str(&format!("{OPTIONAL_DERIVES}\n"));
+        } else {
+            code.push_str(&format!("{DEFAULT_DERIVES}\n"));
+        }
+        code.push_str(&format!("pub struct {name} {}\n", CURLY[0]));
 
         // we generate this here because of &self but add it on at the end
         let enum_arg_code = self.get_arg_enums();
+        let check_code = (checked.contains(&name) && !CHECKED.contains(&self.name.as_str()))
+            .then(|| self.check_code(&name, checked));
 
         let field_len = self.fields.len();
         for (i, field) in self.fields.into_iter().enumerate() {
-            code.push_str(&field.to_code());
+            code.push_str(&field.into_code());
 
             if i != (field_len - 1) {
                 code.push_str(",\n");
@@ -265,10 +715,31 @@ impl StructData {
         if !enum_arg_code.is_empty() {
             code.push_str(&enum_arg_code);
         }
+        code.extend(check_code);
 
         code
     }
 
+    /// Implements `crate::check::Check` by checking every field of a `checked` type.
+    fn check_code(&self, name: &str, checked: &BTreeSet<String>) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .filter(|f| checked.contains(f.rust_type.item()))
+            .map(|f| {
+                format!(
+                    "{FN_IDENTATION}crate::check::Check::check(&self.{})?;\n",
+                    field_case(&f.name).0
+                )
+            })
+            .collect::<String>();
+
+        format!(
+            "impl crate::check::Check for {name} {0}\n{IDENTATION}fn check(&self) -> std::result::Result<(), String> {0}\n{fields}{FN_IDENTATION}Ok(())\n{IDENTATION}{1}\n{1}\n",
+            CURLY[0], CURLY[1]
+        )
+    }
+
     fn get_arg_enums(&self) -> String {
         let mut code = String::new();
 
@@ -289,7 +760,7 @@ impl StructData {
                 for (i, variant) in union.iter().enumerate() {
                     code.push_str(&format!("{IDENTATION}#[serde(rename = \"{}\")]\n", variant));
                     code.push_str(IDENTATION);
-                    code.push_str(&to_pascal_case(&variant));
+                    code.push_str(&to_pascal_case(variant));
 
                     if i != (union.len() - 1) {
                         code.push_str(",\n");
@@ -307,8 +778,8 @@ impl StructData {
                 for (i, variant) in _enum.iter().enumerate() {
                     code.push_str(&format!(
                         "{IDENTATION}{}({})",
-                        to_pascal_case(&variant),
-                        schema_type_to_rust(&variant)
+                        to_pascal_case(variant),
+                        schema_type_to_rust(variant)
                     ));
 
                     if i != (_enum.len() - 1) {
@@ -332,16 +803,31 @@ struct Field {
     name: String,
     rust_type: RustType,
     attribute: Option<String>,
+    required: bool,
     type_union: Option<Vec<String>>,
     type_enum: Option<Vec<String>>,
 }
 
 impl Field {
-    fn from_value(struct_key: &str, parent_key: &str, data: &Value) -> Option<Self> {
+    fn from_value(
+        struct_key: &str,
+        parent_key: &str,
+        data: &Value,
+        required: bool,
+    ) -> Option<Self> {
         Some(Field {
             name: parent_key.to_string(),
-            rust_type: RustType::new(&data, Some(struct_key), Some(parent_key))?,
-            attribute: None,
+            rust_type: match FIELD_TYPES
+                .iter()
+                .find(|(s, f, _)| *s == struct_key && *f == parent_key)
+            {
+                Some((_, _, rust_type)) => RustType(rust_type.to_string()),
+                None => RustType::new(data, Some(struct_key), Some(parent_key))?,
+            },
+            attribute: (!required).then(|| {
+                "#[serde(skip_serializing_if = \"Option::is_none\", default)]".to_string()
+            }),
+            required,
             type_union: if data.get("type").unwrap_or(&Value::Null).is_array() {
                 Some(
                     data.get("type")?
@@ -363,7 +849,7 @@ impl Field {
         })
     }
 
-    fn to_code(self) -> String {
+    fn into_code(self) -> String {
         let mut field = String::new();
 
         if let Some(attr) = self.attribute {
@@ -378,11 +864,19 @@ impl Field {
             ));
         }
 
-        field.push_str(&format!(
-            "{IDENTATION}pub {}: {}",
-            field_name,
-            self.rust_type.inner()
-        ));
+        if self.required {
+            field.push_str(&format!(
+                "{IDENTATION}pub {}: {}",
+                field_name,
+                self.rust_type.inner()
+            ));
+        } else {
+            field.push_str(&format!(
+                "{IDENTATION}pub {}: Option<{}>",
+                field_name,
+                self.rust_type.inner()
+            ));
+        }
 
         field
     }
@@ -414,7 +908,7 @@ impl EnumData {
         })
     }
 
-    fn to_code(self) -> String {
+    fn into_code(self) -> String {
         let mut code = String::new();
 
         if self.enum_type != "string" {
@@ -452,13 +946,866 @@ impl EnumData {
     }
 }
 
+/// Accessors that don't read well when named straight after their endpoint segment.
+const GROUP_RENAMES: &[(&str, &str)] = &[("serversettings", "settings")];
+
+/// A node in the endpoint path tree, e.g. `serversettings` -> `motd` -> `set`.
+///
+/// Every node with children becomes its own borrowed sub-client,
+/// while leaf nodes become methods on the sub-client of their parent.
+#[derive(Debug, Default)]
+struct ClientGroup {
+    segment: String,
+    /// The endpoint path, as in the schema.
+    endpoint: Vec<String>,
+    /// The endpoint path with [`GROUP_RENAMES`] applied, used for naming.
+    path: Vec<String>,
+    function: Option<FunctionData>,
+    children: Vec<ClientGroup>,
+}
+
+impl ClientGroup {
+    fn insert(&mut self, segments: &[String], function: FunctionData) {
+        let Some((segment, rest)) = segments.split_first() else {
+            self.function = Some(function);
+            return;
+        };
+
+        let index = match self.children.iter().position(|c| &c.segment == segment) {
+            Some(index) => index,
+            None => {
+                let mut endpoint = self.endpoint.clone();
+                endpoint.push(segment.clone());
+                let mut path = self.path.clone();
+                path.push(group_name(segment));
+                self.children.push(ClientGroup {
+                    segment: segment.clone(),
+                    endpoint,
+                    path,
+                    ..Default::default()
+                });
+                self.children.len() - 1
+            }
+        };
+
+        self.children[index].insert(rest, function);
+    }
+
+    fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    fn struct_name(&self) -> String {
+        if self.is_root() {
+            "Client".to_string()
+        } else {
+            format!("{}Client", to_pascal_case(&self.path.join("_")))
+        }
+    }
+
+    fn into_code(self) -> String {
+        let mut code = String::new();
+        // the root is the base client itself, sub-clients borrow it
+        // sub-clients are `Copy` so their futures only borrow the base client
+        let (receiver, client) = if self.is_root() {
+            ("&self", "self")
+        } else {
+            ("self", "self.0")
+        };
+        let endpoint = format!("minecraft:{}", self.endpoint.join("/"));
+
+        if !self.is_root() {
+            code.push_str(&format!("/// Methods under `{endpoint}`.\n"));
+            code.push_str("#[derive(Debug, Clone, Copy)]\n");
+            code.push_str(&format!(
+                "pub struct {}<'a>(&'a Client);\n",
+                self.struct_name()
+            ));
+        }
+
+        // sub-clients hand out the lifetime of the base client, not their own
+        let (lifetime, borrow) = if self.is_root() {
+            ("", "'_")
+        } else {
+            ("<'a>", "'a")
+        };
+        code.push_str(&format!(
+            "impl{lifetime} {}{lifetime} {}\n",
+            self.struct_name(),
+            CURLY[0]
+        ));
+
+        if let Some(function) = self.function {
+            code.push_str(&function.into_code("get", receiver, client));
+        }
+
+        let mut sub_clients = String::new();
+        for mut child in self.children {
+            if child.children.is_empty() {
+                if let Some(function) = child.function.take() {
+                    code.push_str(&function.into_code(
+                        &field_case(&child.segment).0,
+                        receiver,
+                        client,
+                    ));
+                }
+                continue;
+            }
+
+            code.push_str(&format!(
+                "{IDENTATION}/// Methods under `minecraft:{}`, see [`{}`].\n",
+                child.endpoint.join("/"),
+                child.struct_name()
+            ));
+            code.push_str(&format!(
+                "{IDENTATION}pub fn {}({receiver}) -> {}<{borrow}> {}\n{FN_IDENTATION}{}({client})\n{IDENTATION}{}\n",
+                field_case(child.path.last().unwrap()).0,
+                child.struct_name(),
+                CURLY[0],
+                child.struct_name(),
+                CURLY[1]
+            ));
+
+            sub_clients.push_str(&child.into_code());
+        }
+
+        code.push_str(&format!("{}\n", CURLY[1]));
+        code.push_str(&sub_clients);
+
+        code
+    }
+}
+
+/// A variant of the generated `ServerEvent` enum, one per notification method.
 #[derive(Debug)]
-struct FunctionData {
+struct EventVariant {
     doc: String,
     name: String,
     endpoint: String,
+    /// The name and type of the single param the notification carries, if any.
+    param: Option<(String, RustType)>,
+}
+
+impl EventVariant {
+    fn from_value(data: &Value) -> Option<Self> {
+        let doc = data.get("description")?.as_str()?.to_string();
+        let endpoint = data.get("name")?.as_str()?.to_string();
+
+        // `minecraft:notification/players/joined` -> `PlayerJoined`
+        // while the `server` ones drop the group, `minecraft:notification/server/status` -> `Status`
+        let path = endpoint.split('/').skip(1).collect::<Vec<&str>>();
+        let name = match path.as_slice() {
+            ["server", action] => to_pascal_case(action),
+            [group, action] => to_pascal_case(&format!(
+                "{}_{action}",
+                group.strip_suffix('s').unwrap_or(group)
+            )),
+            _ => return None,
+        };
+
+        let param = match data.get("params")?.as_array()?.first() {
+            Some(param) => Some((
+                param.get("name")?.as_str()?.to_string(),
+                RustType::new(param.get("schema")?, None, None)?,
+            )),
+            None => None,
+        };
+
+        Some(EventVariant {
+            doc,
+            name,
+            endpoint,
+            param,
+        })
+    }
+}
+
+fn events_code(variants: Vec<EventVariant>) -> String {
+    let mut code = String::new();
+
+    code.push_str("/// A notification sent by the server, see [`Client::events`].\n");
+    code.push_str("#[derive(Debug, Clone, PartialEq, Eq, Hash)]\n");
+    code.push_str(&format!("pub enum ServerEvent {}\n", CURLY[0]));
+    for variant in &variants {
+        code.push_str(&format!("{IDENTATION}/// {}\n", variant.doc));
+        match &variant.param {
+            Some((_, rust_type)) => code.push_str(&format!(
+                "{IDENTATION}{}({}),\n",
+                variant.name,
+                rust_type.inner()
+            )),
+            None => code.push_str(&format!("{IDENTATION}{},\n", variant.name)),
+        }
+    }
+    code.push_str(&format!("{}\n", CURLY[1]));
+
+    code.push_str(&format!("impl ServerEvent {}\n", CURLY[0]));
+    code.push_str(&format!(
+        "{IDENTATION}/// Every `minecraft:notification/*` method.\n{IDENTATION}pub const METHODS: &[&str] = &[{}];\n\n",
+        variants
+            .iter()
+            .map(|v| format!("\"{}\"", v.endpoint))
+            .collect::<Vec<String>>()
+            .join(", ")
+    ));
+
+    code.push_str(&format!(
+        "{IDENTATION}/// Returns the notification method this event is sent as.\n{IDENTATION}pub fn method(&self) -> &'static str {}\n{FN_IDENTATION}match self {}\n",
+        CURLY[0], CURLY[0]
+    ));
+    for variant in &variants {
+        let pattern = if variant.param.is_some() { "(_)" } else { "" };
+        code.push_str(&format!(
+            "{FN_IDENTATION}{IDENTATION}Self::{}{pattern} => \"{}\",\n",
+            variant.name, variant.endpoint
+        ));
+    }
+    code.push_str(&format!(
+        "{FN_IDENTATION}{}\n{IDENTATION}{}\n\n",
+        CURLY[1], CURLY[1]
+    ));
+
+    code.push_str(&format!(
+        "{IDENTATION}/// Returns the positional `params` this event is sent with.\n{IDENTATION}pub fn params(&self) -> serde_json::Value {}\n{FN_IDENTATION}match self {}\n",
+        CURLY[0], CURLY[0]
+    ));
+    for variant in &variants {
+        match &variant.param {
+            Some(_) => code.push_str(&format!(
+                "{FN_IDENTATION}{IDENTATION}Self::{}(param) => serde_json::json!([param]),\n",
+                variant.name
+            )),
+            None => code.push_str(&format!(
+                "{FN_IDENTATION}{IDENTATION}Self::{} => serde_json::json!([]),\n",
+                variant.name
+            )),
+        }
+    }
+    code.push_str(&format!(
+        "{FN_IDENTATION}{}\n{IDENTATION}{}\n\n",
+        CURLY[1], CURLY[1]
+    ));
+
+    code.push_str(&format!(
+        r#"    /// Decodes the `params` of a notification sent as `method`.
+    ///
+    /// Returns `None` if `method` isn't a known notification.
+    pub fn from_notification(method: &str, params: serde_json::Value) -> Option<StdResult<Self, serde_json::Error>> {0}
+        Some(match method {0}
+"#,
+        CURLY[0]
+    ));
+    for variant in &variants {
+        match &variant.param {
+            Some((name, _)) => code.push_str(&format!(
+                "{FN_IDENTATION}{IDENTATION}\"{}\" => notification_param(params, \"{name}\").map(Self::{}),\n",
+                variant.endpoint, variant.name
+            )),
+            None => code.push_str(&format!(
+                "{FN_IDENTATION}{IDENTATION}\"{}\" => Ok(Self::{}),\n",
+                variant.endpoint, variant.name
+            )),
+        }
+    }
+    code.push_str(&format!(
+        r#"            _ => return None,
+        {1})
+    {1}
+{1}
+
+impl Notification for ServerEvent {0}
+    fn method(&self) -> &'static str {0}
+        ServerEvent::method(self)
+    {1}
+
+    fn params(&self) -> serde_json::Value {0}
+        ServerEvent::params(self)
+    {1}
+{1}
+
+/// Notification params are either positional, `[param]`, or named, `{0}"name": param{1}`.
+fn notification_param<T: serde::de::DeserializeOwned>(params: serde_json::Value, name: &str) -> StdResult<T, serde_json::Error> {0}
+    let param = match params {0}
+        serde_json::Value::Array(mut params) if !params.is_empty() => params.swap_remove(0),
+        serde_json::Value::Object(mut params) if params.contains_key(name) => params.remove(name).unwrap_or_default(),
+        params => params,
+    {1};
+    serde_json::from_value(param)
+{1}
+
+impl Client {0}
+    /// Returns a single [`Stream`] of every notification the server sends, as [`ServerEvent`]s.
+    ///
+    /// Every notification stream of a [`Client`] shares the same subscriptions,
+    /// if this stream falls behind it yields [`NotificationError::Lagged`] for the events it missed.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use mc_rpc::{0}ServerEvent, StreamExt{1};
+    /// # async fn example(client: mc_rpc::Client) -> mc_rpc::Result<()> {0}
+    /// let mut events = client.events().await?;
+    /// while let Some(Ok(event)) = events.next().await {0}
+    ///     match event {0}
+    ///         ServerEvent::PlayerJoined(player) => println!("{0}player:?{1} joined"),
+    ///         ServerEvent::Saved => println!("Server just got saved"),
+    ///         _ => (),
+    ///     {1}
+    /// {1}
+    /// # Ok(())
+    /// # {1}
+    /// ```
+    pub async fn events(&self) -> Result<impl Stream<Item = StdResult<ServerEvent, NotificationError>> + use<>> {0}
+        self.notifications(ServerEvent::from_notification).await
+    {1}
+
+    /// Returns a [`Stream`] of every notification `decode` picks out of the shared subscriptions.
+    async fn notifications<T, F>(&self, decode: F) -> Result<impl Stream<Item = StdResult<T, NotificationError>> + use<T, F>>
+    where
+        F: Fn(&str, serde_json::Value) -> Option<StdResult<T, serde_json::Error>>,
+    {0}
+        let receiver = self.1.subscribe(&self.0, ServerEvent::METHODS).await?;
+        Ok(decode_stream(receiver, decode))
+    {1}
+{1}
+"#,
+        CURLY[0], CURLY[1]
+    ));
+
+    code
+}
+
+/// Generates the `ManagementHandler` trait, one method per request, and the `Dispatcher` routing requests to it.
+fn handler_code(functions: &[FunctionData]) -> String {
+    let mut methods = String::new();
+    let mut arms = String::new();
+
+    for function in functions {
+        if !matches!(function.function_type, FunctionType::Request) {
+            continue;
+        }
+        // flat names since a trait can't be namespaced
+        let name = function.flat_name();
+        let args = std::iter::once("&self".to_string())
+            .chain(function.params.iter().map(|p| p.clone().into_code()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        methods.push_str(&format!(
+            "{IDENTATION}/// {}\n{IDENTATION}fn {name}({args}) -> impl Future<Output = StdResult<{}, HandlerError>> + Send {}\n{FN_IDENTATION}async {} Err(HandlerError::method_not_found(\"{}\")) {}\n{IDENTATION}{}\n",
+            function.doc,
+            function.return_type.inner(),
+            CURLY[0],
+            CURLY[0],
+            function.endpoint,
+            CURLY[1],
+            CURLY[1],
+        ));
+
+        let params = function
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("request_param(&params, {i}, \"{}\")?", p.raw_name))
+            .collect::<Vec<String>>()
+            .join(", ");
+        arms.push_str(&format!(
+            "{FN_IDENTATION}{IDENTATION}\"{}\" => to_result(self.0.{name}({params}).await),\n",
+            function.endpoint
+        ));
+    }
+
+    format!(
+        r#"
+/// The server half of the protocol, one method per request.
+///
+/// Every method defaults to [`HandlerError::method_not_found`], so implementations only need the methods they support.
+/// Requests are routed to it by a [`Dispatcher`], and notifications are sent with a [`Notifier`].
+///
+/// ## Example
+/// ```no_run
+/// use mc_rpc::{0}Dispatcher, HandlerError, ManagementHandler, Player{1};
+///
+/// struct Lobby;
+///
+/// impl ManagementHandler for Lobby {0}
+///     async fn players(&self) -> Result<Vec<Player>, HandlerError> {0}
+///         Ok(vec![])
+///     {1}
+/// {1}
+///
+/// # async fn example() -> Result<(), HandlerError> {0}
+/// let dispatcher = Dispatcher::new(Lobby);
+/// let players = dispatcher.call("minecraft:players", serde_json::Value::Null).await?;
+/// # Ok(())
+/// # {1}
+/// ```
+#[allow(unused_variables)]
+pub trait ManagementHandler: Send + Sync {0}
+{methods}{1}
+
+/// Routes incoming JSON-RPC requests to a [`ManagementHandler`].
+///
+/// The dispatcher is transport agnostic, feed it every text message a connection receives
+/// and send back whatever [`handle`](Dispatcher::handle) returns.
+#[derive(Debug)]
+pub struct Dispatcher<H>(std::sync::Arc<H>);
+
+impl<H> Clone for Dispatcher<H> {0}
+    fn clone(&self) -> Self {0}
+        Self(self.0.clone())
+    {1}
+{1}
+
+impl<H> Dispatcher<H> {0}
+    pub fn new(handler: H) -> Self {0}
+        Self(std::sync::Arc::new(handler))
+    {1}
+
+    pub fn handler(&self) -> &H {0}
+        &self.0
+    {1}
+{1}
+
+impl<H: ManagementHandler> Dispatcher<H> {0}
+    /// Handles a single JSON-RPC message, returning the response to send back.
+    ///
+    /// The message may also be a batch, an array of requests, which is answered with an array of responses.
+    /// Returns `None` for requests without an `id`, which don't expect a response.
+    pub async fn handle(&self, message: &str) -> Option<String> {0}
+        handle_message(message, move |request| self.handle_request(request)).await
+    {1}
+
+    async fn handle_request(&self, request: serde_json::Value) -> Option<serde_json::Value> {0}
+        let (id, method, params) = match parse_request(request) {0}
+            Ok(request) => request,
+            Err(response) => return Some(response),
+        {1};
+
+        let result = self.call(&method, params).await;
+        id.map(|id| response(id, result))
+    {1}
+
+    /// Calls the handler method for `method` with its JSON-RPC `params`, either named or positional.
+    ///
+    /// `rpc.discover` is answered with the [`SCHEMA`] these bindings were generated from.
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> StdResult<serde_json::Value, HandlerError> {0}
+        match method {0}
+            "rpc.discover" => serde_json::from_str(SCHEMA).map_err(|e| HandlerError::internal(e.to_string())),
+{arms}            _ => Err(HandlerError::method_not_found(method)),
+        {1}
+    {1}
+{1}
+
+impl<H: ManagementHandler + 'static> MessageHandler for Dispatcher<H> {0}
+    fn handle(&self, message: &str) -> impl Future<Output = Option<String>> + Send {0}
+        Dispatcher::handle(self, message)
+    {1}
+{1}
+"#,
+        CURLY[0], CURLY[1]
+    )
+}
+
+fn batch_code(functions: &[FunctionData]) -> String {
+    let mut methods = String::new();
+
+    for function in functions {
+        if !matches!(function.function_type, FunctionType::Request) {
+            continue;
+        }
+        let args = std::iter::once("&mut self".to_string())
+            .chain(function.params.iter().map(|p| p.clone().into_code()))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let params = function
+            .params
+            .iter()
+            .map(|p| format!("(\"{}\", {})", p.raw_name, p.to_value_code()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        methods.push_str(&format!(
+            "{IDENTATION}/// {}\n{IDENTATION}pub fn {}({args}) -> BatchEntry<{}> {}\n{FN_IDENTATION}self.1.push(\"{}\", vec![{params}])\n{IDENTATION}{}\n",
+            function.doc,
+            function.flat_name(),
+            function.return_type.inner(),
+            CURLY[0],
+            function.endpoint,
+            CURLY[1],
+        ));
+    }
+
+    format!(
+        r#"
+impl Client {0}
+    /// Starts a [`Batch`] of requests, sent together in a single message.
+    pub fn batch(&self) -> Batch<'_> {0}
+        Batch(self, BatchRequests::default())
+    {1}
+{1}
+
+/// Requests sent together as a single JSON-RPC batch, one method per request.
+///
+/// Adding a request returns a [`BatchEntry`] to get its result from the [`BatchResults`] with.
+/// Each entry succeeds or fails on its own, so a failed entry doesn't fail the batch.
+/// The batch is sent on a second connection to the same server, opened with the first batch and shared by every clone of the client,
+/// and has to be answered within the timeout of the client it was started from.
+/// Whenever that connection can't be opened, or the server rejects the batch, the requests are sent one at a time on the client's own connection instead.
+///
+/// ## Example
+/// ```no_run
+/// # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {0}
+/// let mut batch = client.batch();
+/// let players = batch.players();
+/// let motd = batch.settings_motd();
+/// let results = batch.send().await?;
+///
+/// for (index, method, error) in results.errors() {0}
+///     println!("Entry {0}index{1} ({0}method{1}) failed: {0}error{1}");
+/// {1}
+/// let players = results.get(players)?;
+/// let motd = results.get(motd)?;
+/// # Ok(())
+/// # {1}
+/// ```
+#[derive(Debug)]
+pub struct Batch<'a>(&'a Client, BatchRequests);
+
+impl Batch<'_> {0}
+{methods}
+    /// Sends every request added so far, returning their results once all of them are answered.
+    ///
+    /// Fails as a whole if a param couldn't be serialized, or the server couldn't be reached.
+    pub async fn send(self) -> Result<BatchResults> {0}
+        self.1.send(&self.0.0, &self.0.2).await
+    {1}
+{1}
+"#,
+        CURLY[0], CURLY[1]
+    )
+}
+
+fn settings_code(functions: &[FunctionData]) -> String {
+    let mut fields = String::new();
+    let mut endpoints = String::new();
+
+    // every `serversettings/<name>` getter with a `serversettings/<name>/set` setter
+    for getter in functions {
+        let [group, name] = getter.path.as_slice() else {
+            continue;
+        };
+        if group != "serversettings" {
+            continue;
+        }
+        let Some(setter) = functions
+            .iter()
+            .find(|f| f.path.len() == 3 && f.path[..2] == getter.path[..] && f.path[2] == "set")
+        else {
+            continue;
+        };
+        let Some(param) = setter.params.first() else {
+            continue;
+        };
+
+        let (field, renamed) = field_case(name);
+        // "Get the server's view distance" describes the field as "The server's view distance"
+        let doc = getter.doc.strip_prefix("Get ").unwrap_or(&getter.doc);
+        let mut doc_chars = doc.chars();
+        let doc = doc_chars
+            .next()
+            .map(|first| first.to_uppercase().chain(doc_chars).collect::<String>())
+            .unwrap_or_default();
+        fields.push_str(&format!(
+            "{IDENTATION}/// {doc}\n{IDENTATION}#[serde(skip_serializing_if = \"Option::is_none\", default)]\n"
+        ));
+        if renamed {
+            fields.push_str(&format!("{IDENTATION}#[serde(rename = \"{name}\")]\n"));
+        }
+        fields.push_str(&format!(
+            "{IDENTATION}pub {field}: Option<{}>,\n",
+            getter.return_type.inner()
+        ));
+        endpoints.push_str(&format!(
+            "{FN_IDENTATION}SettingEndpoints {} field: \"{name}\", get: \"{}\", set: \"{}\", param: \"{}\" {},\n",
+            CURLY[0], getter.endpoint, setter.endpoint, param.raw_name, CURLY[1]
+        ));
+    }
+
+    format!(
+        r#"
+/// Every `minecraft:serversettings/*` value, `None` for values that aren't known or should be left alone.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct ServerSettings {0}
+{fields}{1}
+
+impl ServerSettings {0}
+    /// The getter and setter of every field, by its serialized name.
+    pub const ENDPOINTS: &[SettingEndpoints] = &[
+{endpoints}    ];
+{1}
+"#,
+        CURLY[0], CURLY[1]
+    )
+}
+
+fn gateway_code(functions: &[FunctionData]) -> String {
+    let mut routes = String::new();
+    let mut table = String::new();
+    let mut taken: Vec<(&str, String)> = vec![];
+
+    for function in functions {
+        if !matches!(function.function_type, FunctionType::Request) {
+            continue;
+        }
+        let (http_method, path) = function.gateway_route();
+        assert!(
+            !taken.contains(&(http_method, path.clone())),
+            "{} has the same gateway route as another method, {http_method} {path}",
+            function.endpoint
+        );
+        taken.push((http_method, path.clone()));
+
+        // the body is the only param itself, or an array of all of them
+        let names = function
+            .params
+            .iter()
+            .map(|p| p.name.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let body = match function.params.as_slice() {
+            [] => String::new(),
+            [param] => format!(", Json({}): Json<{}>", param.name, param.rust_type.inner()),
+            params => format!(
+                ", Json(({names})): Json<({})>",
+                params
+                    .iter()
+                    .map(|p| p.rust_type.inner().to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        };
+
+        routes.push_str(&format!(
+            "{FN_IDENTATION}{IDENTATION}.route(\"{path}\", routing::{}(|State(client): State<Client>{body}| async move {} gateway_response(client{}({names}).await) {}))\n",
+            http_method.to_lowercase(),
+            CURLY[0],
+            function.call_path(functions),
+            CURLY[1],
+        ));
+        table.push_str(&format!(
+            "{IDENTATION}GatewayRoute {} http_method: \"{http_method}\", path: \"{path}\", method: \"{}\", params: &[{}] {},\n",
+            CURLY[0],
+            function.endpoint,
+            function
+                .params
+                .iter()
+                .map(|p| format!("\"{}\"", p.raw_name))
+                .collect::<Vec<String>>()
+                .join(", "),
+            CURLY[1]
+        ));
+    }
+
+    format!(
+        r#"
+/// Every request as a route of the REST gateway, see [`Client::gateway`].
+#[cfg(feature = "gateway")]
+pub const GATEWAY_ROUTES: &[GatewayRoute] = &[
+{table}];
+
+#[cfg(feature = "gateway")]
+impl Client {0}
+    /// Returns a REST gateway forwarding to this client, an [`axum::Router`] with a route per request of [`GATEWAY_ROUTES`].
+    ///
+    /// Each route takes its param as the JSON body, or an array of its params if it has several, and answers with the JSON result.
+    /// Notifications are streamed from `GET /events` as Server-Sent Events named after their method, like `players/joined`,
+    /// with their params as data, and `GET /openapi.json` describes every route, see [`gateway_openapi`].
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {0}
+    /// // GET /allowlist, POST /bans, PUT /settings/motd, POST /server/stop, ...
+    /// let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+    /// axum::serve(listener, client.gateway()).await?;
+    /// # Ok(())
+    /// # {1}
+    /// ```
+    pub fn gateway(&self) -> axum::Router {0}
+        use axum::{0}Json, extract::State, routing{1};
+
+        axum::Router::new()
+{routes}            .route("/events", routing::get(|State(client): State<Client>| async move {0} gateway_events(client.events().await) {1}))
+            .route("/openapi.json", routing::get(|| async {0} Json(gateway_openapi()) {1}))
+            .with_state(self.clone())
+    {1}
+{1}
+
+/// The OpenAPI document of [`Client::gateway`], with the descriptions and JSON schemas of the [`SCHEMA`].
+#[cfg(feature = "gateway")]
+pub fn gateway_openapi() -> serde_json::Value {0}
+    crate::gateway::openapi(SCHEMA, GATEWAY_ROUTES)
+{1}
+"#,
+        CURLY[0], CURLY[1]
+    )
+}
+
+fn fleet_code(functions: &[FunctionData]) -> String {
+    let mut methods = String::new();
+
+    for function in functions {
+        if !matches!(function.function_type, FunctionType::Request) {
+            continue;
+        }
+        let args = std::iter::once("&self".to_string())
+            .chain(function.params.iter().map(|p| p.clone().into_code()))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let names = function
+            .params
+            .iter()
+            .map(|p| p.name.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
+        // every server gets params of its own
+        let clones = function
+            .params
+            .iter()
+            .filter(|p| !p.rust_type.is_copy())
+            .map(|p| {
+                format!(
+                    "{FN_IDENTATION}{IDENTATION}let {0} = {0}.clone();\n",
+                    p.name
+                )
+            })
+            .collect::<String>();
+        let call = format!(
+            "async move {} client{}({names}).await {}",
+            CURLY[0],
+            function.call_path(functions),
+            CURLY[1]
+        );
+        let body = if clones.is_empty() {
+            format!("{FN_IDENTATION}self.fan_out(|client| {call}).await\n")
+        } else {
+            format!(
+                "{FN_IDENTATION}self.fan_out(|client| {}\n{clones}{FN_IDENTATION}{IDENTATION}{call}\n{FN_IDENTATION}{}).await\n",
+                CURLY[0], CURLY[1]
+            )
+        };
+
+        methods.push_str(&format!(
+            "{IDENTATION}/// {}\n{IDENTATION}pub async fn {}({args}) -> FleetResults<{}> {}\n{body}{IDENTATION}{}\n",
+            function.doc,
+            function.flat_name(),
+            function.return_type.inner(),
+            CURLY[0],
+            CURLY[1],
+        ));
+    }
+
+    format!(
+        r#"
+impl FleetSelection<'_, Client> {0}
+{methods}{1}
+
+impl Fleet<Client> {0}
+    /// Whether the client of every server is connected, by server name, see [`Client::is_connected`].
+    pub async fn connected(&self) -> std::collections::BTreeMap<String, bool> {0}
+        let checks = self
+            .clients()
+            .map(|(name, client)| async move {0} (name.to_string(), client.is_connected().await) {1});
+        futures_util::future::join_all(checks).await.into_iter().collect()
+    {1}
+
+    /// A stream of every change of a server's connection, tagged with its name,
+    /// `false` once its client disconnected and `true` once it reconnected.
+    ///
+    /// Changes missed because the stream fell behind are left out, check [`connected`](Self::connected) to catch up.
+    pub fn connection_changes(&self) -> impl Stream<Item = (String, bool)> + use<> {0}
+        let disconnects = self.clients().map(|(name, client)| {0}
+            (name.to_string(), client.on_disconnect().filter_map(|change| std::future::ready(change.ok().map(|_| false))))
+        {1});
+        let reconnects = self.clients().map(|(name, client)| {0}
+            (name.to_string(), client.on_reconnect().filter_map(|change| std::future::ready(change.ok().map(|_| true))))
+        {1});
+        futures_util::stream::select(merge(disconnects.collect()), merge(reconnects.collect()))
+    {1}
+
+    /// A single stream of the [`ServerEvent`]s of every server, each tagged with the name of its server.
+    ///
+    /// Servers whose notifications couldn't be subscribed to, e.g. because they were disconnected, have their errors returned
+    /// alongside the stream, and are subscribed to again once their client reconnects.
+    /// Subscriptions outlive reconnects, so every other server stays in the stream until its client is closed.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use mc_rpc::{0}ServerEvent, StreamExt{1};
+    /// # async fn example(fleet: mc_rpc::Fleet) {0}
+    /// let (mut events, errors) = fleet.events().await;
+    /// for (server, error) in errors {0}
+    ///     println!("{0}server{1}: {0}error{1}");
+    /// {1}
+    /// while let Some((server, Ok(event))) = events.next().await {0}
+    ///     if let ServerEvent::PlayerJoined(player) = event {0}
+    ///         println!("{0}player:?{1} joined {0}server{1}");
+    ///     {1}
+    /// {1}
+    /// # {1}
+    /// ```
+    pub async fn events(
+        &self,
+    ) -> (
+        impl Stream<Item = (String, StdResult<ServerEvent, NotificationError>)> + use<>,
+        std::collections::BTreeMap<String, PaleError>,
+    ) {0}
+        let subscriptions = self.clients().map(|(name, client)| async move {0}
+            // listening before subscribing, so a reconnect right after a failed subscription isn't missed
+            let reconnects = client.on_reconnect();
+            (name.to_string(), client.events().await, reconnects)
+        {1});
+
+        let mut streams = vec![];
+        let mut errors = std::collections::BTreeMap::new();
+        for (name, subscription, reconnects) in futures_util::future::join_all(subscriptions).await {0}
+            match subscription {0}
+                Ok(events) => streams.push((name, events.left_stream())),
+                Err(error) => {0}
+                    errors.insert(name.clone(), error);
+                    let resubscribed = reconnects
+                        .filter_map(|client| std::future::ready(client.ok()))
+                        .filter_map(|client| async move {0} client.events().await.ok() {1})
+                        .take(1)
+                        .flatten();
+                    streams.push((name, resubscribed.right_stream()));
+                {1}
+            {1}
+        {1}
+        (merge(streams), errors)
+    {1}
+{1}
+"#,
+        CURLY[0], CURLY[1]
+    )
+}
+
+fn group_name(segment: &str) -> String {
+    GROUP_RENAMES
+        .iter()
+        .find(|(from, _)| *from == segment)
+        .map(|(_, to)| to.to_string())
+        .unwrap_or_else(|| segment.to_string())
+}
+
+#[derive(Debug)]
+struct FunctionData {
+    doc: String,
+    path: Vec<String>,
+    endpoint: String,
     function_type: FunctionType,
     params: Vec<FunctionParam>,
+    /// The name of the single param a notification carries, if any.
+    notification_param: Option<String>,
     return_type: RustType,
 }
 
@@ -467,6 +1814,8 @@ struct FunctionParam {
     raw_name: String,
     name: String,
     rust_type: RustType,
+    /// Whether the type is one of [`checked_types`], checked before it is sent.
+    checked: bool,
 }
 
 #[derive(Debug)]
@@ -478,27 +1827,30 @@ enum FunctionType {
 impl FunctionData {
     fn from_value(data: &Value) -> Option<Self> {
         let doc = data.get("description")?.as_str()?.to_string();
-        let name = data
+        let path = data
             .get("name")?
             .as_str()?
             .trim_start_matches("minecraft:")
-            .replace('/', "_");
+            .split('/')
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
         let endpoint = data.get("name")?.as_str()?.to_string();
 
-        let function_type = if name.starts_with("notification") {
+        let function_type = if path[0] == "notification" {
             FunctionType::Notification
         } else {
             FunctionType::Request
         };
-        println!("{doc:?}, {name:?}, {endpoint:?}, {function_type:?}");
+        println!("{doc:?}, {path:?}, {endpoint:?}, {function_type:?}");
 
+        let mut notification_param = None;
         let (params, return_type) = match function_type {
             FunctionType::Request => {
                 let params: Vec<FunctionParam> = data
                     .get("params")?
                     .as_array()?
                     .iter()
-                    .map(|p| FunctionParam::from_value(p))
+                    .map(FunctionParam::from_value)
                     .collect::<Option<Vec<FunctionParam>>>()?;
                 let return_type = if let Some(return_data) = data.get("result") {
                     RustType::new(return_data.get("schema")?, None, None)?
@@ -512,7 +1864,8 @@ impl FunctionData {
                 // the param in notifications IS the return type since they dont have any params
                 (
                     vec![],
-                    if let Some(result) = data.get("params")?.as_array()?.get(0) {
+                    if let Some(result) = data.get("params")?.as_array()?.first() {
+                        notification_param = Some(result.get("name")?.as_str()?.to_string());
                         RustType::new(result.as_object()?.get("schema")?, None, None)?
                     } else {
                         RustType::new_empty()
@@ -523,38 +1876,97 @@ impl FunctionData {
 
         Some(FunctionData {
             doc,
-            name,
+            path,
             endpoint,
             function_type,
             params,
+            notification_param,
             return_type,
         })
     }
 
-    fn to_code(self) -> String {
+    /// The endpoint path as a single name with [`GROUP_RENAMES`] applied, e.g. `settings_motd_set`.
+    fn flat_name(&self) -> String {
+        self.path
+            .iter()
+            .map(|s| group_name(s))
+            .collect::<Vec<String>>()
+            .join("_")
+    }
+
+    /// The calls reaching this method from the base `Client`, e.g. `.settings().motd().set`.
+    fn call_path(&self, functions: &[FunctionData]) -> String {
+        let (last, groups) = self.path.split_last().unwrap();
+        let mut call = groups
+            .iter()
+            .map(|s| format!(".{}()", field_case(&group_name(s)).0))
+            .collect::<String>();
+
+        // a method with methods under it is the `get` of its own sub-client
+        let is_group = functions
+            .iter()
+            .any(|f| f.path.len() > self.path.len() && f.path.starts_with(&self.path));
+        if is_group {
+            call.push_str(&format!(".{}().get", field_case(&group_name(last)).0));
+        } else {
+            call.push_str(&format!(".{}", field_case(last).0));
+        }
+        call
+    }
+
+    /// The HTTP method and path of this request in the REST gateway, with [`GROUP_RENAMES`] applied.
+    ///
+    /// `set`, `add` and `remove` are `PUT`, `POST` and `DELETE` on their parent, e.g. `PUT /settings/motd`,
+    /// getters without params are `GET` and everything else is a `POST`, e.g. `POST /server/stop`.
+    fn gateway_route(&self) -> (&'static str, String) {
+        let mut path = self
+            .path
+            .iter()
+            .map(|s| group_name(s))
+            .collect::<Vec<String>>();
+        let verb = match path.last().map(|s| s.as_str()) {
+            Some("set") => Some("PUT"),
+            Some("add") => Some("POST"),
+            Some("remove") => Some("DELETE"),
+            _ => None,
+        };
+
+        let http_method = match verb {
+            Some(verb) if path.len() > 1 => {
+                path.pop();
+                verb
+            }
+            _ if self.params.is_empty() && self.doc.starts_with("Get ") => "GET",
+            _ => "POST",
+        };
+        (http_method, format!("/{}", path.join("/")))
+    }
+
+    /// `client` is the expression that reaches the base `Client` from `receiver`.
+    fn into_code(self, name: &str, receiver: &str, client: &str) -> String {
         let mut code = String::new();
 
-        let mut args = vec!["&self".to_string()];
+        let mut args = vec![receiver.to_string()];
         args.append(
             &mut self
                 .params
                 .clone()
                 .into_iter()
-                .map(|s| s.to_code())
+                .map(|s| s.into_code())
                 .collect::<Vec<String>>(),
         );
 
         code.push_str(&format!("{IDENTATION}/// {}\n", self.doc));
         code.push_str(&format!(
             "{IDENTATION}pub async fn {}({}) -> Result<",
-            field_case(&self.name).0,
+            name,
             args.join(", ")
         ));
 
         match self.function_type {
             FunctionType::Notification => {
                 code.push_str(&format!(
-                    "impl Stream<Item = Option<std::result::Result<Vec<{}>, serde_json::Error>>>",
+                    "impl Stream<Item = StdResult<{}, NotificationError>> + use<>",
                     self.return_type.inner()
                 ));
             }
@@ -566,15 +1978,24 @@ impl FunctionData {
 
         match self.function_type {
             FunctionType::Notification => {
+                let decode = match &self.notification_param {
+                    Some(param) => format!(
+                        "|method, params| (method == \"{}\").then(|| notification_param(params, \"{param}\"))",
+                        self.endpoint
+                    ),
+                    None => format!(
+                        "|method, _| (method == \"{}\").then_some(Ok(()))",
+                        self.endpoint
+                    ),
+                };
                 code.push_str(&format!(
-                    "{FN_IDENTATION}self.0.subscribe(\"{}\").await",
-                    self.endpoint
+                    "{FN_IDENTATION}{client}\n{FN_IDENTATION}{IDENTATION}.notifications({decode})\n{FN_IDENTATION}{IDENTATION}.await"
                 ));
             }
             FunctionType::Request => {
                 if self.params.is_empty() {
                     code.push_str(&format!(
-                        "{FN_IDENTATION}self.0.request(\"{}\", None).await",
+                        "{FN_IDENTATION}{client}.request(\"{}\", None).await",
                         self.endpoint
                     ));
                 } else {
@@ -584,13 +2005,14 @@ impl FunctionData {
                     );
                     for param in self.params {
                         code.push_str(&format!(
-                            "{FN_IDENTATION}map.insert(\"{}\".to_string(), serde_json::to_value(&{})?);\n",
-                            param.raw_name, param.name
+                            "{FN_IDENTATION}map.insert(\"{}\".to_string(), {}?);\n",
+                            param.raw_name,
+                            param.to_value_code()
                         ));
                     }
 
                     code.push_str(&format!(
-                        "{FN_IDENTATION}self.0.request(\"{}\", Some(map)).await",
+                        "{FN_IDENTATION}{client}.request(\"{}\", Some(map)).await",
                         self.endpoint
                     ));
                 }
@@ -617,10 +2039,20 @@ impl FunctionParam {
             raw_name,
             name,
             rust_type,
+            checked: false,
         })
     }
 
-    fn to_code(self) -> String {
+    /// The expression serializing this param into a `serde_json::Result<Value>`, checking it first if it is `checked`.
+    fn to_value_code(&self) -> String {
+        if self.checked {
+            format!("crate::check::to_value({})", self.name)
+        } else {
+            format!("serde_json::to_value({})", self.name)
+        }
+    }
+
+    fn into_code(self) -> String {
         format!("{}: {}", self.name, self.rust_type.inner())
     }
 }
@@ -6,6 +6,7 @@ use std::fs::File;
 use std::io::BufWriter;
 
 #[tokio::main]
+#[allow(clippy::result_large_err)]
 async fn main() -> Result<()> {
     dotenvy::dotenv().unwrap();
 
@@ -15,16 +16,25 @@ async fn main() -> Result<()> {
     )
     .await?;
     let schema: Value = client.request("rpc.discover", None).await?;
+    let version = schema["info"]["version"]
+        .as_str()
+        .expect("Schema is missing info.version");
+
+    // every version gets its own file, generated into its own module
     let formatter = PrettyFormatter::with_indent(b"    ");
     let mut serializer = Serializer::with_formatter(
         BufWriter::new(
-            File::create(concat!(env!("CARGO_MANIFEST_DIR"), "/../schema.json")).unwrap(),
+            File::create(format!(
+                "{}/../schemas/{version}.json",
+                env!("CARGO_MANIFEST_DIR")
+            ))
+            .unwrap(),
         ),
         formatter,
     );
     schema
         .serialize(&mut serializer)
-        .expect("Failed to write new schema.json file");
+        .expect("Failed to write new schema file");
 
     Ok(())
 }
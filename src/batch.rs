@@ -0,0 +1,459 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    result::Result as StdResult,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures_util::{SinkExt, StreamExt, stream::SplitSink};
+use pale::{PaleError, RPCError, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+use thiserror::Error;
+use tokio::{
+    net::TcpStream,
+    sync::{Mutex, mpsc},
+};
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream,
+    tungstenite::{ClientRequestBuilder, Message, http::Uri},
+};
+
+use crate::request::Requests;
+
+/// The id of the next batch, so responses to a batch that was given up on are never taken for those of a later one.
+static NEXT_BATCH: AtomicU64 = AtomicU64::new(0);
+
+/// A request added to a `Batch`, exchanged for its result with [`BatchResults::get`] once the batch is sent.
+pub struct BatchEntry<T> {
+    batch: u64,
+    index: usize,
+    result: PhantomData<fn() -> T>,
+}
+
+impl<T> BatchEntry<T> {
+    /// The position of the entry in its batch.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T> Clone for BatchEntry<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BatchEntry<T> {}
+
+impl<T> fmt::Debug for BatchEntry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchEntry")
+            .field("batch", &self.batch)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+/// Why a single entry of a sent batch has no result, the other entries are unaffected.
+#[derive(Debug, Error)]
+pub enum BatchError {
+    /// The server answered the entry with an error.
+    #[error("{} ({})", .0.message, .0.code)]
+    Rpc(RPCError),
+    /// The server answered the entry with an error that isn't a JSON-RPC error object.
+    #[error("Malformed error: {0}")]
+    Malformed(Value),
+    /// The server didn't answer the entry.
+    #[error("No response")]
+    Missing,
+    /// The result doesn't match the type of the entry.
+    #[error(transparent)]
+    Decode(#[from] serde_json::Error),
+    /// The entry was added to another batch than the one these are the results of.
+    #[error("Entry of another batch")]
+    OtherBatch,
+}
+
+/// How a single entry was answered.
+#[derive(Debug, Clone, PartialEq)]
+enum Answer {
+    Result(Value),
+    Error(RPCError),
+    /// An `error` that couldn't be read as an [`RPCError`], kept as is so only its own entry fails.
+    Malformed(Value),
+}
+
+impl Answer {
+    fn from_response(response: &Value) -> Self {
+        match response.get("error") {
+            Some(error) => match serde_json::from_value(error.clone()) {
+                Ok(error) => Self::Error(error),
+                Err(_) => Self::Malformed(error.clone()),
+            },
+            None => Self::Result(response.get("result").cloned().unwrap_or_default()),
+        }
+    }
+}
+
+/// The error of an entry as if its request had been sent on its own.
+impl From<BatchError> for PaleError {
+    fn from(error: BatchError) -> Self {
+        match error {
+            BatchError::Rpc(error) => Self::RPC(error),
+            BatchError::Decode(error) => Self::SerdeJson(error),
+            BatchError::Malformed(_) | BatchError::Missing | BatchError::OtherBatch => {
+                Self::NoReturnedRPCData
+            }
+        }
+    }
+}
+
+/// The requests of a `Batch` that hasn't been sent yet.
+#[derive(Debug)]
+pub(crate) struct BatchRequests {
+    /// Ties the entries to the results of this batch.
+    batch: u64,
+    requests: Vec<(&'static str, Option<Map<String, Value>>)>,
+    /// The first param that couldn't be serialized, returned by [`send`](Self::send).
+    error: Option<serde_json::Error>,
+}
+
+impl Default for BatchRequests {
+    fn default() -> Self {
+        Self {
+            batch: NEXT_BATCH.fetch_add(1, Ordering::Relaxed),
+            requests: vec![],
+            error: None,
+        }
+    }
+}
+
+impl BatchRequests {
+    /// Adds a request of `method` with its named `params`.
+    pub(crate) fn push<T>(
+        &mut self,
+        method: &'static str,
+        params: Vec<(&str, serde_json::Result<Value>)>,
+    ) -> BatchEntry<T> {
+        let mut named = Map::new();
+        for (name, param) in params {
+            match param {
+                Ok(param) => {
+                    named.insert(name.to_string(), param);
+                }
+                Err(e) => {
+                    self.error.get_or_insert(e);
+                }
+            }
+        }
+
+        self.requests
+            .push((method, (!named.is_empty()).then_some(named)));
+        BatchEntry {
+            batch: self.batch,
+            index: self.requests.len() - 1,
+            result: PhantomData,
+        }
+    }
+
+    /// Sends every request as a single JSON-RPC batch to the server `client` is connected to.
+    ///
+    /// The whole exchange gives up once the timeout of `requests` has passed.
+    pub(crate) async fn send(
+        self,
+        client: &pale::Client,
+        requests: &Requests,
+    ) -> Result<BatchResults> {
+        if let Some(e) = self.error {
+            return Err(e.into());
+        }
+
+        let methods = self
+            .requests
+            .iter()
+            .map(|(method, _)| *method)
+            .collect::<Vec<&str>>();
+        // an empty batch is an invalid request, there is nothing to ask anyway
+        if methods.is_empty() {
+            return Ok(BatchResults {
+                batch: self.batch,
+                methods,
+                answers: vec![],
+            });
+        }
+
+        let answers = tokio::time::timeout(
+            requests.timeout(client),
+            requests
+                .batches()
+                .send(client, requests, self.batch, self.requests),
+        )
+        .await
+        .map_err(|_| PaleError::RequestTimeout)??;
+
+        Ok(BatchResults {
+            batch: self.batch,
+            methods,
+            answers,
+        })
+    }
+}
+
+type WebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// The connection batches are sent on, shared by every clone of a `Client` like its [`Requests`].
+///
+/// The connection of the underlying client expects a single response per message, so it can't carry batches.
+/// Instead a second connection is opened with the same uri and config on the first batch and reused by every later one,
+/// until either connection is lost or the client is closed.
+/// Whenever it can't be used, the requests of a batch are sent one at a time on the client's own connection instead,
+/// which reconnects on its own.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BatchConnection(Arc<Mutex<BatchState>>);
+
+#[derive(Debug, Default)]
+enum BatchState {
+    #[default]
+    Closed,
+    Open {
+        write: SplitSink<WebSocket, Message>,
+        /// Every message the server sent on the connection, notifications left out.
+        responses: mpsc::Receiver<Value>,
+    },
+    /// The server rejected a batch, so it doesn't support them and every later batch is sent one request at a time.
+    Unsupported,
+}
+
+impl BatchConnection {
+    /// Closes the connection, the next batch opens a new one.
+    pub(crate) async fn close(&self) {
+        let mut state = self.0.lock().await;
+        if let BatchState::Open { .. } = *state {
+            *state = BatchState::Closed;
+        }
+    }
+
+    /// Sends `requests` as batch `batch` and returns the answer of each, `None` for those the server didn't answer.
+    ///
+    /// Batches are sent one at a time, like single requests.
+    async fn send(
+        &self,
+        client: &pale::Client,
+        individual: &Requests,
+        batch: u64,
+        requests: Vec<(&'static str, Option<Map<String, Value>>)>,
+    ) -> Result<Vec<Option<Answer>>> {
+        let mut state = self.0.lock().await;
+        if !client.is_connected().await {
+            if let BatchState::Open { .. } = *state {
+                *state = BatchState::Closed;
+            }
+            return Err(PaleError::ClientDisconnected);
+        }
+
+        if let BatchState::Closed = *state {
+            match open(client).await {
+                Ok(open) => *state = open,
+                // the client is connected, so its own connection can still take the requests
+                Err(_) => return one_by_one(client, individual, requests).await,
+            }
+        }
+        let BatchState::Open { write, responses } = &mut *state else {
+            return one_by_one(client, individual, requests).await;
+        };
+
+        let message = requests
+            .iter()
+            .enumerate()
+            .map(|(index, (method, params))| {
+                let mut request =
+                    json!({ "jsonrpc": "2.0", "id": format!("{batch}/{index}"), "method": method });
+                if let Some(params) = params {
+                    request["params"] = Value::Object(params.clone());
+                }
+                request
+            })
+            .collect::<Vec<Value>>();
+        if write
+            .send(Message::text(Value::Array(message).to_string()))
+            .await
+            .is_err()
+        {
+            // nothing was sent, so the requests can't have been handled twice
+            *state = BatchState::Closed;
+            return one_by_one(client, individual, requests).await;
+        }
+
+        loop {
+            let Some(response) = responses.recv().await else {
+                // the batch may have been handled already, sending it again could apply it twice
+                *state = BatchState::Closed;
+                return Err(PaleError::ClientDisconnected);
+            };
+
+            match response {
+                Value::Array(responses) => {
+                    if let Some(answers) = answers(batch, requests.len(), &responses) {
+                        return Ok(answers);
+                    }
+                    // the late responses of a batch that was given up on
+                }
+                // the batch as a whole was rejected, e.g. by a server that doesn't support batches
+                Value::Object(response) if response.get("id").is_none_or(Value::is_null) => {
+                    *state = BatchState::Unsupported;
+                    return one_by_one(client, individual, requests).await;
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+/// Opens a connection to the server `client` is connected to, with the same uri and config.
+async fn open(client: &pale::Client) -> Result<BatchState> {
+    let mut request = ClientRequestBuilder::new(Uri::from_str(&client.uri)?);
+    if let Some(bearer) = &client.config.bearer_token {
+        request = request.with_header("Authorization", format!("Bearer {bearer}"));
+    }
+    let (ws, _) =
+        tokio_tungstenite::connect_async_with_config(request, Some(client.config.ws_config), false)
+            .await?;
+
+    let (write, read) = ws.split();
+    let (sender, responses) = mpsc::channel(client.config.channel_capacity);
+    // reads until the connection is lost or the `BatchState` holding the receiver is dropped
+    let closed = {
+        let sender = sender.clone();
+        async move { sender.closed().await }
+    };
+    tokio::spawn(async move {
+        let mut read = std::pin::pin!(read.take_until(closed));
+        while let Some(Ok(message)) = read.next().await {
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let Ok(response) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            // notifications are sent to every connection
+            if response.get("method").is_some() {
+                continue;
+            }
+            if sender.send(response).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(BatchState::Open { write, responses })
+}
+
+/// Picks the answers of batch `batch` with `len` requests out of `responses`,
+/// or returns `None` if they belong to another batch.
+fn answers(batch: u64, len: usize, responses: &[Value]) -> Option<Vec<Option<Answer>>> {
+    let prefix = format!("{batch}/");
+    let mut answers = vec![None; len];
+    let mut ours = false;
+    for response in responses {
+        let Some(answer) = response
+            .get("id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| id.strip_prefix(&prefix))
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| answers.get_mut(index))
+        else {
+            continue;
+        };
+        *answer = Some(Answer::from_response(response));
+        ours = true;
+    }
+
+    ours.then_some(answers)
+}
+
+/// Sends `requests` one at a time on the connection of `client`, failing as a whole only if the server can't be reached.
+async fn one_by_one(
+    client: &pale::Client,
+    individual: &Requests,
+    requests: Vec<(&'static str, Option<Map<String, Value>>)>,
+) -> Result<Vec<Option<Answer>>> {
+    let mut answers = Vec::with_capacity(requests.len());
+    for (method, params) in requests {
+        let params = params.map(|params| params.into_iter().collect::<HashMap<String, Value>>());
+        answers.push(Some(
+            match individual.send::<Value>(client, method, params).await {
+                Ok(result) => Answer::Result(result),
+                Err(PaleError::RPC(error)) => Answer::Error(error),
+                Err(e) => return Err(e),
+            },
+        ));
+    }
+
+    Ok(answers)
+}
+
+/// The results of a sent `Batch`, one per entry.
+///
+/// Entries fail on their own, check [`is_ok`](Self::is_ok) or [`errors`](Self::errors) for partial failures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResults {
+    batch: u64,
+    methods: Vec<&'static str>,
+    /// `None` for entries the server didn't answer.
+    answers: Vec<Option<Answer>>,
+}
+
+impl BatchResults {
+    /// Returns the result of `entry`, failing with [`BatchError::OtherBatch`] if it was added to another batch.
+    pub fn get<T: DeserializeOwned>(&self, entry: BatchEntry<T>) -> StdResult<T, BatchError> {
+        if entry.batch != self.batch {
+            return Err(BatchError::OtherBatch);
+        }
+        match self.answers.get(entry.index) {
+            Some(Some(Answer::Result(result))) => Ok(serde_json::from_value(result.clone())?),
+            Some(Some(Answer::Error(error))) => Err(BatchError::Rpc(error.clone())),
+            Some(Some(Answer::Malformed(error))) => Err(BatchError::Malformed(error.clone())),
+            Some(None) | None => Err(BatchError::Missing),
+        }
+    }
+
+    /// The number of entries in the batch.
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    /// Whether the server answered every entry without an error.
+    pub fn is_ok(&self) -> bool {
+        self.answers
+            .iter()
+            .all(|answer| matches!(answer, Some(Answer::Result(_))))
+    }
+
+    /// The index, method and error of every entry the server answered with an error or not at all, in batch order.
+    pub fn errors(&self) -> impl Iterator<Item = (usize, &'static str, BatchError)> + '_ {
+        self.answers
+            .iter()
+            .zip(&self.methods)
+            .enumerate()
+            .filter_map(|(index, (answer, method))| match answer {
+                Some(Answer::Result(_)) => None,
+                Some(Answer::Error(error)) => {
+                    Some((index, *method, BatchError::Rpc(error.clone())))
+                }
+                Some(Answer::Malformed(error)) => {
+                    Some((index, *method, BatchError::Malformed(error.clone())))
+                }
+                None => Some((index, *method, BatchError::Missing)),
+            })
+    }
+}
@@ -0,0 +1,365 @@
+//! `mc-rpc dashboard`, a live view of a server with actions on its online players, built with the `tui` feature.
+
+use std::{collections::VecDeque, pin::pin};
+
+use mc_rpc::{
+    Client, KickPlayer, Message, Operator, Player, ServerEvent, ServerState, StreamExt,
+    SystemMessage, UserBan,
+};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListState, Paragraph},
+};
+use tokio::sync::mpsc;
+
+use crate::{CliResult, cell};
+
+/// How many events the log keeps.
+const LOG_SIZE: usize = 200;
+
+const HELP: &str = "↑↓ select  k kick  b ban  o op  m message  M broadcast  r refresh  q quit";
+
+/// An action that needs some text first.
+#[derive(Clone, Copy)]
+enum Action {
+    Kick,
+    Ban,
+    Message,
+    Broadcast,
+}
+
+struct Prompt {
+    action: Action,
+    player: Option<Player>,
+    input: String,
+}
+
+struct Dashboard {
+    client: Client,
+    state: ServerState,
+    players: ListState,
+    log: VecDeque<String>,
+    prompt: Option<Prompt>,
+    /// The outcome of the last action.
+    result: String,
+}
+
+/// Shows the server's status and notifications until the user quits.
+pub async fn run(client: Client) -> CliResult<()> {
+    let state = client.server().status().await?;
+    let mut dashboard = Dashboard {
+        client,
+        state,
+        players: ListState::default().with_selected(Some(0)),
+        log: VecDeque::new(),
+        prompt: None,
+        result: String::new(),
+    };
+
+    // reading the terminal blocks, so it gets a thread of its own
+    let (tx, terminal_events) = mpsc::channel(16);
+    std::thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if tx.blocking_send(event).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut terminal = ratatui::init();
+    let result = dashboard.run(&mut terminal, terminal_events).await;
+    ratatui::restore();
+    result
+}
+
+impl Dashboard {
+    async fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        mut terminal_events: mpsc::Receiver<Event>,
+    ) -> CliResult<()> {
+        let mut events = pin!(self.client.events().await?);
+        let mut connected = true;
+
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            tokio::select! {
+                event = terminal_events.recv() => match event {
+                    Some(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                        if !self.key(key).await {
+                            return Ok(());
+                        }
+                    }
+                    // anything else, like a resize, only needs a redraw
+                    Some(_) => {}
+                    None => return Ok(()),
+                },
+                event = events.next(), if connected => match event {
+                    Some(Ok(event)) => self.event(event),
+                    Some(Err(e)) => self.push_log(format!("error: {e}")),
+                    None => {
+                        connected = false;
+                        self.result = "Disconnected from the server".to_string();
+                    }
+                },
+            }
+        }
+    }
+
+    fn online(&self) -> &[Player] {
+        self.state.players.as_deref().unwrap_or_default()
+    }
+
+    fn selected(&self) -> Option<Player> {
+        self.players
+            .selected()
+            .and_then(|index| self.online().get(index))
+            .cloned()
+    }
+
+    fn push_log(&mut self, line: String) {
+        if self.log.len() == LOG_SIZE {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+    }
+
+    fn event(&mut self, event: ServerEvent) {
+        match &event {
+            ServerEvent::PlayerJoined(player) => {
+                let players = self.state.players.get_or_insert_default();
+                if !players.iter().any(|p| p.matches(player)) {
+                    players.push(player.clone());
+                }
+            }
+            ServerEvent::PlayerLeft(player) => {
+                if let Some(players) = &mut self.state.players {
+                    players.retain(|p| !p.matches(player));
+                }
+            }
+            ServerEvent::Started => self.state.started = true,
+            ServerEvent::Stopping => self.state.started = false,
+            ServerEvent::Status(state) => self.state = state.clone(),
+            _ => {}
+        }
+
+        // keep the selection on the list
+        let count = self.online().len();
+        match self.players.selected() {
+            _ if count == 0 => self.players.select(None),
+            Some(index) if index >= count => self.players.select(Some(count - 1)),
+            None => self.players.select(Some(0)),
+            Some(_) => {}
+        }
+
+        let method = event.method().trim_start_matches("minecraft:notification/");
+        let params = match event.params() {
+            serde_json::Value::Array(params) => params.iter().map(cell).collect::<Vec<String>>(),
+            params => vec![cell(&params)],
+        };
+        let line = format!("{method} {}", params.join(" "));
+        self.push_log(line.trim_end().to_string());
+    }
+
+    /// Handles a key press, returns `false` to quit.
+    async fn key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+
+        if let Some(prompt) = &mut self.prompt {
+            match key.code {
+                KeyCode::Enter => {
+                    if let Some(prompt) = self.prompt.take() {
+                        self.result = self.finish(prompt).await;
+                    }
+                }
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Backspace => {
+                    prompt.input.pop();
+                }
+                KeyCode::Char(c) => prompt.input.push(c),
+                _ => {}
+            }
+            return true;
+        }
+
+        let player = self.selected();
+        let prompt = |action| Prompt {
+            action,
+            player: player.clone(),
+            input: String::new(),
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up => self.players.select_previous(),
+            KeyCode::Down
+                if self
+                    .players
+                    .selected()
+                    .is_some_and(|i| i + 1 < self.online().len()) =>
+            {
+                self.players.select_next();
+            }
+            KeyCode::Char('r') => {
+                self.result = match self.client.server().status().await {
+                    Ok(state) => {
+                        self.state = state;
+                        "Refreshed".to_string()
+                    }
+                    Err(e) => format!("Refreshing failed: {e}"),
+                };
+            }
+            KeyCode::Char('M') => self.prompt = Some(prompt(Action::Broadcast)),
+            KeyCode::Char('k' | 'b' | 'o' | 'm') if player.is_none() => {
+                self.result = "No player selected".to_string();
+            }
+            KeyCode::Char('k') => self.prompt = Some(prompt(Action::Kick)),
+            KeyCode::Char('b') => self.prompt = Some(prompt(Action::Ban)),
+            KeyCode::Char('m') => self.prompt = Some(prompt(Action::Message)),
+            KeyCode::Char('o') => {
+                let player = player.unwrap_or_default();
+                let operator = Operator {
+                    bypasses_player_limit: None,
+                    permission_level: None,
+                    player: player.clone(),
+                };
+                self.result = match self.client.operators().add(vec![operator]).await {
+                    Ok(_) => format!("Opped {}", name(&player)),
+                    Err(e) => format!("Opping {} failed: {e}", name(&player)),
+                };
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Runs the action of a confirmed prompt, returning its outcome.
+    async fn finish(&self, prompt: Prompt) -> String {
+        let text = (!prompt.input.trim().is_empty()).then_some(prompt.input);
+        let player = prompt.player.unwrap_or_default();
+        let name = name(&player);
+
+        let result = match prompt.action {
+            Action::Kick => {
+                let kick = KickPlayer {
+                    message: text.map(Message::from),
+                    player: player.clone(),
+                };
+                self.client.players().kick(vec![kick]).await.map(|_| ())
+            }
+            Action::Ban => {
+                let mut ban = UserBan::permanent(player.clone(), "");
+                ban.reason = text;
+                self.client.bans().add(vec![ban]).await.map(|_| ())
+            }
+            Action::Message | Action::Broadcast => {
+                let Some(text) = text else {
+                    return "Nothing to send".to_string();
+                };
+                let message = SystemMessage {
+                    receiving_players: matches!(prompt.action, Action::Message)
+                        .then(|| vec![player.clone()]),
+                    ..Message::from(text).into()
+                };
+                self.client
+                    .server()
+                    .system_message(message)
+                    .await
+                    .map(|_| ())
+            }
+        };
+
+        let done = match prompt.action {
+            Action::Kick => format!("Kicked {name}"),
+            Action::Ban => format!("Banned {name}"),
+            Action::Message => format!("Sent a message to {name}"),
+            Action::Broadcast => "Sent a message to everyone".to_string(),
+        };
+        match result {
+            Ok(()) => done,
+            Err(e) => format!("Failed: {e}"),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .areas(frame.area());
+        let [players, log] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(body);
+
+        let version = match &self.state.version {
+            Some(version) => format!("{} (protocol {})", version.name, version.protocol),
+            None => "unknown version".to_string(),
+        };
+        let status = if self.state.started {
+            "running".green()
+        } else {
+            "stopped".red()
+        };
+        let summary = Line::from(vec![
+            status,
+            format!("  {version}  {} online", self.online().len()).into(),
+        ]);
+        frame.render_widget(
+            Paragraph::new(summary).block(Block::bordered().title(" mc-rpc ")),
+            header,
+        );
+
+        let items = self.online().iter().map(name).collect::<Vec<String>>();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Players "))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, players, &mut self.players);
+
+        // the newest events that fit
+        let height = log.height.saturating_sub(2) as usize;
+        let lines = self
+            .log
+            .iter()
+            .skip(self.log.len().saturating_sub(height))
+            .map(|line| Line::from(line.as_str()))
+            .collect::<Vec<Line>>();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Events ")),
+            log,
+        );
+
+        let first = match &self.prompt {
+            Some(prompt) => {
+                let target = prompt.player.as_ref().map(name).unwrap_or_default();
+                let label = match prompt.action {
+                    Action::Kick => format!("Kick {target}, message"),
+                    Action::Ban => format!("Ban {target}, reason"),
+                    Action::Message => format!("Message to {target}"),
+                    Action::Broadcast => "Message to everyone".to_string(),
+                };
+                Line::from(format!("{label}: {}█", prompt.input))
+            }
+            None => Line::from(self.result.as_str()),
+        };
+        let help = match self.prompt {
+            Some(_) => "enter confirm  esc cancel",
+            None => HELP,
+        };
+        frame.render_widget(Paragraph::new(vec![first, help.dim().into()]), footer);
+    }
+}
+
+fn name(player: &Player) -> String {
+    match (&player.name, &player.id) {
+        (Some(name), _) => name.clone(),
+        (None, Some(id)) => id.to_string(),
+        (None, None) => "unknown".to_string(),
+    }
+}
@@ -0,0 +1,810 @@
+//! `mc-rpc`, a command-line tool for a server's management protocol, built with the `cli` feature.
+//!
+//! The url and secret are taken from `--url` and `--secret`, `MC_RPC_URL` and `MC_RPC_SECRET`,
+//! or a profile of the config file:
+//! ```toml
+//! [profiles.default]
+//! url = "ws://localhost:25585"
+//! secret = "<secret>"
+//! ```
+
+use std::{collections::BTreeMap, error::Error, path::PathBuf, process::ExitCode, time::Duration};
+
+use clap::{Args, Parser, Subcommand};
+use mc_rpc::{
+    Client, ClientConfig, IncomingIpBan, KickPlayer, Message, Operator, Player, PlayerId,
+    ServerPlan, ServerSettings, StreamExt, SystemMessage, UntypedGameRule, UntypedGameRuleValue,
+    UserBan,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+#[cfg(feature = "tui")]
+mod dashboard;
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "mc-rpc",
+    version,
+    about = "Manage a Minecraft server over its management protocol"
+)]
+struct Cli {
+    #[command(flatten)]
+    connection: Connection,
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Connection {
+    /// The management server's url, e.g. `ws://localhost:25585`
+    #[arg(long, env = "MC_RPC_URL", global = true)]
+    url: Option<String>,
+    /// The management server's secret
+    #[arg(long, env = "MC_RPC_SECRET", global = true, hide_env_values = true)]
+    secret: Option<String>,
+    /// The profile of the config file to connect with
+    #[arg(long, env = "MC_RPC_PROFILE", global = true)]
+    profile: Option<String>,
+    /// The config file, `$XDG_CONFIG_HOME/mc-rpc/config.toml` or `~/.config/mc-rpc/config.toml` by default
+    #[arg(long, env = "MC_RPC_CONFIG", global = true)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// The players allowed to join
+    #[command(subcommand)]
+    Allowlist(AllowlistCommand),
+    /// The operators
+    #[command(subcommand)]
+    Operators(OperatorsCommand),
+    /// The banned players
+    #[command(subcommand)]
+    Bans(BansCommand),
+    /// The banned ips
+    #[command(subcommand)]
+    IpBans(IpBansCommand),
+    /// The online players
+    #[command(subcommand)]
+    Players(PlayersCommand),
+    /// The server itself
+    #[command(subcommand)]
+    Server(ServerCommand),
+    /// The server settings
+    #[command(subcommand)]
+    Settings(SettingsCommand),
+    /// The game rules
+    #[command(subcommand)]
+    Gamerule(GameruleCommand),
+    /// Prints notifications as they arrive
+    #[command(subcommand)]
+    Watch(WatchCommand),
+    /// A live view of the server and its players
+    #[cfg(feature = "tui")]
+    Dashboard,
+    /// Serves a REST gateway to the server, with notifications at `/events`
+    #[cfg(feature = "gateway")]
+    Gateway {
+        /// The address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+    },
+    /// Serves Prometheus metrics of the server at `/metrics`
+    #[cfg(feature = "metrics")]
+    Metrics {
+        /// The address to listen on
+        #[arg(long, default_value = "127.0.0.1:9100")]
+        listen: String,
+        /// How often the status and settings are polled, like 30s or 1m
+        #[arg(long, value_parser = parse_duration, default_value = "15s")]
+        interval: Duration,
+    },
+}
+
+#[derive(Subcommand)]
+enum AllowlistCommand {
+    List,
+    /// Adds players by name or uuid
+    Add {
+        #[arg(required = true)]
+        players: Vec<String>,
+    },
+    /// Removes players by name or uuid
+    Remove {
+        #[arg(required = true)]
+        players: Vec<String>,
+    },
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum OperatorsCommand {
+    List,
+    /// Ops players by name or uuid
+    Add {
+        #[arg(required = true)]
+        players: Vec<String>,
+        /// The permission level, 1 to 4
+        #[arg(long)]
+        level: Option<i32>,
+        /// Whether they can join a full server
+        #[arg(long)]
+        bypass_player_limit: bool,
+    },
+    /// Deops players by name or uuid
+    Remove {
+        #[arg(required = true)]
+        players: Vec<String>,
+    },
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum BansCommand {
+    List,
+    /// Bans players by name or uuid
+    Add {
+        #[arg(required = true)]
+        players: Vec<String>,
+        #[arg(long)]
+        reason: Option<String>,
+        /// How long the ban lasts, e.g. `30m`, `12h` or `7d`, permanent if left out
+        #[arg(long, value_parser = parse_duration)]
+        duration: Option<Duration>,
+    },
+    /// Pardons players by name or uuid
+    Remove {
+        #[arg(required = true)]
+        players: Vec<String>,
+    },
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum IpBansCommand {
+    List,
+    /// Bans ips
+    Add {
+        #[arg(required = true)]
+        ips: Vec<String>,
+        #[arg(long)]
+        reason: Option<String>,
+        /// How long the ban lasts, e.g. `30m`, `12h` or `7d`, permanent if left out
+        #[arg(long, value_parser = parse_duration)]
+        duration: Option<Duration>,
+    },
+    /// Pardons ips
+    Remove {
+        #[arg(required = true)]
+        ips: Vec<String>,
+    },
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum PlayersCommand {
+    List,
+    /// Kicks players by name or uuid
+    Kick {
+        #[arg(required = true)]
+        players: Vec<String>,
+        #[arg(long)]
+        message: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ServerCommand {
+    /// Whether the server is started, its version and online players
+    Status,
+    /// Saves the world
+    Save {
+        /// Waits for everything to be written to disk
+        #[arg(long)]
+        flush: bool,
+    },
+    Stop,
+    /// Sends a system message to every player
+    Message {
+        text: String,
+        /// Shows it above the hotbar instead of in chat
+        #[arg(long)]
+        overlay: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SettingsCommand {
+    /// Prints every setting, or only `name`
+    Get { name: Option<String> },
+    /// Sets a setting, e.g. `motd "Hello"` or `max_players 20`
+    Set { name: String, value: String },
+}
+
+#[derive(Subcommand)]
+enum GameruleCommand {
+    List,
+    Get {
+        key: String,
+    },
+    /// Sets a game rule, e.g. `keepInventory true`
+    Set {
+        key: String,
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum WatchCommand {
+    /// Every notification, one per line
+    Events,
+}
+
+#[derive(Deserialize, Default)]
+struct Config {
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+struct Profile {
+    url: Option<String>,
+    secret: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> CliResult<()> {
+    let client = connect(&cli.connection).await?;
+    let json = cli.json;
+
+    match cli.command {
+        Command::Allowlist(command) => {
+            let allowlist = client.allowlist();
+            let players = match command {
+                AllowlistCommand::List => allowlist.get().await?,
+                AllowlistCommand::Add { players } => allowlist.add(to_players(&players)).await?,
+                AllowlistCommand::Remove { players } => {
+                    allowlist.remove(to_players(&players)).await?
+                }
+                AllowlistCommand::Clear => allowlist.clear().await?,
+            };
+            print(&players, json)
+        }
+        Command::Operators(command) => {
+            let operators = client.operators();
+            let list = match command {
+                OperatorsCommand::List => operators.get().await?,
+                OperatorsCommand::Add {
+                    players,
+                    level,
+                    bypass_player_limit,
+                } => {
+                    let add = to_players(&players)
+                        .into_iter()
+                        .map(|player| Operator {
+                            bypasses_player_limit: bypass_player_limit.then_some(true),
+                            permission_level: level,
+                            player,
+                        })
+                        .collect();
+                    operators.add(add).await?
+                }
+                OperatorsCommand::Remove { players } => {
+                    operators.remove(to_players(&players)).await?
+                }
+                OperatorsCommand::Clear => operators.clear().await?,
+            };
+            print(&list, json)
+        }
+        Command::Bans(command) => {
+            let bans = client.bans();
+            let list = match command {
+                BansCommand::List => bans.get().await?,
+                BansCommand::Add {
+                    players,
+                    reason,
+                    duration,
+                } => {
+                    let add = to_players(&players)
+                        .into_iter()
+                        .map(|player| {
+                            let mut ban = match duration {
+                                Some(duration) => UserBan::temporary(player, duration, ""),
+                                None => UserBan::permanent(player, ""),
+                            };
+                            ban.reason = reason.clone();
+                            ban
+                        })
+                        .collect();
+                    bans.add(add).await?
+                }
+                BansCommand::Remove { players } => bans.remove(to_players(&players)).await?,
+                BansCommand::Clear => bans.clear().await?,
+            };
+            print(&list, json)
+        }
+        Command::IpBans(command) => {
+            let ip_bans = client.ip_bans();
+            let list = match command {
+                IpBansCommand::List => ip_bans.get().await?,
+                IpBansCommand::Add {
+                    ips,
+                    reason,
+                    duration,
+                } => {
+                    let add = ips
+                        .into_iter()
+                        .map(|ip| {
+                            let mut ban = match duration {
+                                Some(duration) => IncomingIpBan::temporary(ip, duration, ""),
+                                None => IncomingIpBan::permanent(ip, ""),
+                            };
+                            ban.reason = reason.clone();
+                            ban
+                        })
+                        .collect();
+                    ip_bans.add(add).await?
+                }
+                IpBansCommand::Remove { ips } => ip_bans.remove(ips).await?,
+                IpBansCommand::Clear => ip_bans.clear().await?,
+            };
+            print(&list, json)
+        }
+        Command::Players(command) => match command {
+            PlayersCommand::List => print(&client.players().get().await?, json),
+            PlayersCommand::Kick { players, message } => {
+                let kick = to_players(&players)
+                    .into_iter()
+                    .map(|player| KickPlayer {
+                        message: message.clone().map(Message::from),
+                        player,
+                    })
+                    .collect();
+                print(&client.players().kick(kick).await?, json)
+            }
+        },
+        Command::Server(command) => match command {
+            ServerCommand::Status => print(&client.server().status().await?, json),
+            ServerCommand::Save { flush } => print(&client.server().save(flush).await?, json),
+            ServerCommand::Stop => print(&client.server().stop().await?, json),
+            ServerCommand::Message { text, overlay } => {
+                let message = SystemMessage {
+                    overlay: overlay.then_some(true),
+                    ..Message::from(text).into()
+                };
+                print(&client.server().system_message(message).await?, json)
+            }
+        },
+        Command::Settings(command) => match command {
+            SettingsCommand::Get { name } => {
+                let settings = serde_json::to_value(client.settings_snapshot().await?)?;
+                match name {
+                    Some(name) => {
+                        let field = setting_field(&name)?;
+                        let value = settings
+                            .get(field)
+                            .ok_or_else(|| format!("The server doesn't have '{field}'"))?;
+                        print(value, json)
+                    }
+                    None => print(&settings, json),
+                }
+            }
+            SettingsCommand::Set { name, value } => {
+                let field = setting_field(&name)?;
+                let settings = setting(field, &value)
+                    .ok_or_else(|| format!("Invalid value '{value}' for '{field}'"))?;
+                let changes = client.settings_restore(&settings).await?;
+                if json {
+                    let changes = changes
+                        .into_iter()
+                        .map(|change| json!({ "setting": change.setting.field, "from": change.from, "to": change.to }))
+                        .collect::<Vec<Value>>();
+                    print(&changes, json)
+                } else {
+                    // the same diff as a `ServerSpec` plan
+                    let plan = ServerPlan {
+                        settings: changes,
+                        ..Default::default()
+                    };
+                    print!("{plan}");
+                    Ok(())
+                }
+            }
+        },
+        Command::Gamerule(command) => match command {
+            GameruleCommand::List => print(&client.gamerules().get().await?, json),
+            GameruleCommand::Get { key } => {
+                let rules = client.gamerules().get().await?;
+                let rule = rules
+                    .into_iter()
+                    .find(|rule| rule.key == key)
+                    .ok_or_else(|| format!("Unknown game rule '{key}'"))?;
+                print(&rule, json)
+            }
+            GameruleCommand::Set { key, value } => {
+                let value = if let Ok(value) = value.parse::<bool>() {
+                    UntypedGameRuleValue::Boolean(value)
+                } else if let Ok(value) = value.parse::<i32>() {
+                    UntypedGameRuleValue::Integer(value)
+                } else {
+                    return Err(format!(
+                        "Invalid value '{value}', game rules are booleans or integers"
+                    )
+                    .into());
+                };
+                let rule = client
+                    .gamerules()
+                    .update(UntypedGameRule { key, value })
+                    .await?;
+                print(&rule, json)
+            }
+        },
+        Command::Watch(WatchCommand::Events) => {
+            let mut events = client.events().await?;
+            while let Some(event) = events.next().await {
+                let event = event?;
+                let method = event.method().trim_start_matches("minecraft:notification/");
+                // one event per line either way, so the output can be piped
+                if json {
+                    println!("{}", json!({ "method": method, "params": event.params() }));
+                } else {
+                    let params = match event.params() {
+                        Value::Array(params) => params.iter().map(cell).collect::<Vec<String>>(),
+                        params => vec![cell(&params)],
+                    };
+                    println!("{method} {}", params.join(" "));
+                }
+            }
+            Ok(())
+        }
+        #[cfg(feature = "tui")]
+        Command::Dashboard => dashboard::run(client).await,
+        #[cfg(feature = "gateway")]
+        Command::Gateway { listen } => {
+            let listener = tokio::net::TcpListener::bind(&listen).await?;
+            eprintln!("Listening on http://{}", listener.local_addr()?);
+            axum::serve(listener, client.gateway()).await?;
+            Ok(())
+        }
+        #[cfg(feature = "metrics")]
+        Command::Metrics { listen, interval } => {
+            let metrics = mc_rpc::metrics::Metrics::new();
+            let client = client.with_metrics(&metrics);
+            let listener = tokio::net::TcpListener::bind(&listen).await?;
+            eprintln!("Listening on http://{}/metrics", listener.local_addr()?);
+            tokio::select! {
+                served = axum::serve(listener, metrics.router()).into_future() => served?,
+                watched = metrics.watch(&client, interval) => watched?,
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Connects with the url and secret of the flags, the environment or the profile, in that order.
+async fn connect(connection: &Connection) -> CliResult<Client> {
+    let profile = profile(connection)?;
+    let url = connection
+        .url
+        .clone()
+        .or(profile.url)
+        .ok_or("No url, pass --url, set MC_RPC_URL or add it to a profile")?;
+    let config = match connection.secret.clone().or(profile.secret) {
+        Some(secret) => ClientConfig::with_bearer(&secret),
+        None => ClientConfig::default(),
+    };
+    Ok(Client::new(url, config).await?)
+}
+
+/// The profile to connect with, the `default` one if none is given and it exists.
+fn profile(connection: &Connection) -> CliResult<Profile> {
+    let path = connection.config.clone().or_else(|| {
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config.join("mc-rpc").join("config.toml"))
+    });
+
+    let config = match path {
+        Some(path) if path.exists() => {
+            toml::from_str::<Config>(&std::fs::read_to_string(&path)?)
+                .map_err(|e| format!("Invalid config '{}': {e}", path.display()))?
+        }
+        // a config given explicitly has to exist
+        Some(path) if connection.config.is_some() => {
+            return Err(format!("No config at '{}'", path.display()).into());
+        }
+        _ => Config::default(),
+    };
+
+    match &connection.profile {
+        Some(name) => config
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("No profile '{name}'").into()),
+        None => Ok(config.profiles.get("default").cloned().unwrap_or_default()),
+    }
+}
+
+/// A player by uuid if `arg` is one, otherwise by name.
+fn to_player(arg: &str) -> Player {
+    if let Ok(id) = serde_json::from_value::<PlayerId>(arg.into()) {
+        let player = Player::by_id(id);
+        if player.validate().is_ok() {
+            return player;
+        }
+    }
+    Player::by_name(arg)
+}
+
+fn to_players(args: &[String]) -> Vec<Player> {
+    args.iter().map(|arg| to_player(arg)).collect()
+}
+
+/// The serialized name of a setting, with `-` for `_` accepted too.
+fn setting_field(name: &str) -> CliResult<&'static str> {
+    let name = name.replace('-', "_");
+    ServerSettings::ENDPOINTS
+        .iter()
+        .map(|setting| setting.field)
+        .find(|field| *field == name)
+        .ok_or_else(|| format!("Unknown setting '{name}'").into())
+}
+
+/// Settings with only `field` set, to `value` as JSON or else as a string.
+fn setting(field: &str, value: &str) -> Option<ServerSettings> {
+    serde_json::from_str::<Value>(value)
+        .ok()
+        .into_iter()
+        .chain([Value::String(value.to_string())])
+        .find_map(|value| {
+            let settings = Map::from_iter([(field.to_string(), value)]);
+            serde_json::from_value(Value::Object(settings)).ok()
+        })
+}
+
+/// Parses `90`, `90s`, `30m`, `12h` or `7d`.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let (number, unit) = text.split_at(
+        text.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len()),
+    );
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid duration '{text}'"))?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("Invalid duration unit '{unit}', use s, m, h or d")),
+    };
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Invalid duration '{text}'"))
+}
+
+/// Prints `value` as pretty JSON, or as a table for a list, key value rows for an object, or just the value.
+fn print(value: &impl Serialize, json: bool) -> CliResult<()> {
+    let value = serde_json::to_value(value)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    match value {
+        Value::Array(rows) => {
+            let rows = rows
+                .iter()
+                .map(flatten)
+                .collect::<Vec<Vec<(String, String)>>>();
+            // every column any row has, in the order they first appear
+            let mut columns: Vec<String> = vec![];
+            for (column, _) in rows.iter().flatten() {
+                if !columns.contains(column) {
+                    columns.push(column.clone());
+                }
+            }
+            let rows = rows
+                .iter()
+                .map(|row| {
+                    columns
+                        .iter()
+                        .map(|column| {
+                            row.iter()
+                                .find(|(c, _)| c == column)
+                                .map(|(_, value)| value.clone())
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .collect::<Vec<Vec<String>>>();
+            print_table(&columns, &rows);
+        }
+        Value::Object(_) => {
+            let rows = flatten(&value)
+                .into_iter()
+                .map(|(key, value)| vec![key, value])
+                .collect::<Vec<Vec<String>>>();
+            print_table(&[], &rows);
+        }
+        value => println!("{}", cell(&value)),
+    }
+    Ok(())
+}
+
+/// The cells of a row, nested objects as `parent.child` columns.
+fn flatten(value: &Value) -> Vec<(String, String)> {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .flat_map(|(key, value)| match value {
+                Value::Object(_) => flatten(value)
+                    .into_iter()
+                    .map(|(child, value)| (format!("{key}.{child}"), value))
+                    .collect(),
+                value => vec![(key.clone(), cell(value))],
+            })
+            .collect(),
+        value => vec![(String::new(), cell(value))],
+    }
+}
+
+/// A single value, players by name.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        Value::Array(values) => values.iter().map(cell).collect::<Vec<String>>().join(", "),
+        Value::Object(object) => match object.get("name") {
+            Some(name) => cell(name),
+            None => value.to_string(),
+        },
+        value => value.to_string(),
+    }
+}
+
+fn print_table(header: &[String], rows: &[Vec<String>]) {
+    let count = header
+        .len()
+        .max(rows.iter().map(Vec::len).max().unwrap_or(0));
+    let mut widths = vec![0; count];
+    for row in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |row: &[String]| {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<String>>();
+        println!("{}", cells.join("  ").trim_end());
+    };
+    if !header.is_empty() {
+        line(
+            &header
+                .iter()
+                .map(|h| h.to_uppercase())
+                .collect::<Vec<String>>(),
+        );
+    }
+    for row in rows {
+        line(row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_take_a_unit() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * 60 * 60)));
+        assert_eq!(
+            parse_duration("7d"),
+            Ok(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+
+        for invalid in ["", "d", "1w", "1.5h", "-1d", "1 d", "300000000000000d"] {
+            assert!(parse_duration(invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn setting_names_take_dashes_or_underscores() {
+        assert_eq!(setting_field("max-players").unwrap(), "max_players");
+        assert_eq!(setting_field("max_players").unwrap(), "max_players");
+        assert_eq!(setting_field("motd").unwrap(), "motd");
+        assert!(setting_field("max players").is_err());
+        assert!(setting_field("level-seed").is_err());
+    }
+
+    #[test]
+    fn setting_values_are_json_or_raw_strings() {
+        let motd = setting("motd", "Hello").unwrap();
+        assert_eq!(motd.motd.as_deref(), Some("Hello"));
+        let motd = setting("motd", "\"Hello\"").unwrap();
+        assert_eq!(motd.motd.as_deref(), Some("Hello"));
+        // valid JSON of the wrong type is taken as the raw string
+        let motd = setting("motd", "20").unwrap();
+        assert_eq!(motd.motd.as_deref(), Some("20"));
+
+        let max_players = setting("max_players", "20").unwrap();
+        assert_eq!(max_players.max_players, Some(20));
+        assert!(setting("max_players", "many").is_none());
+        assert_eq!(
+            setting("difficulty", "hard").unwrap().difficulty,
+            Some(mc_rpc::Difficulty::Hard)
+        );
+    }
+
+    #[test]
+    fn players_are_ids_or_names() {
+        let id = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+        let by_id = to_player(id);
+        assert_eq!(by_id.id, Some(id.parse().unwrap()));
+        assert_eq!(by_id.name, None);
+
+        let by_name = to_player("Steve");
+        assert_eq!(by_name.id, None);
+        assert_eq!(by_name.name.as_deref(), Some("Steve"));
+        assert_eq!(to_player("069a79f4").name.as_deref(), Some("069a79f4"));
+    }
+
+    #[test]
+    fn nested_objects_become_columns() {
+        let ban = json!({
+            "player": { "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5", "name": "Notch" },
+            "reason": "Griefing",
+            "source": null,
+            "expires": { "at": { "date": "2025-10-18" } },
+        });
+        assert_eq!(
+            flatten(&ban),
+            [
+                ("expires.at.date".to_string(), "2025-10-18".to_string()),
+                (
+                    "player.id".to_string(),
+                    "069a79f4-44e9-4726-a5be-fca90e38aaf5".to_string()
+                ),
+                ("player.name".to_string(), "Notch".to_string()),
+                ("reason".to_string(), "Griefing".to_string()),
+                ("source".to_string(), String::new()),
+            ]
+        );
+        assert_eq!(flatten(&json!(3)), [(String::new(), "3".to_string())]);
+    }
+
+    #[test]
+    fn cells_show_lists_and_players_by_name() {
+        let players = json!([{ "name": "Steve" }, { "name": "Alex", "id": "x" }, { "id": "y" }]);
+        assert_eq!(cell(&players), "Steve, Alex, {\"id\":\"y\"}");
+        assert_eq!(cell(&json!(true)), "true");
+        assert_eq!(cell(&Value::Null), "");
+    }
+}
@@ -0,0 +1,43 @@
+use serde::{Serialize, ser::Error as _};
+use serde_json::Value;
+
+use crate::{Message, Player};
+
+/// A param that is checked before it is sent in a request, so an invalid one fails the request instead of reaching the server.
+///
+/// Implemented by [`Message`] and [`Player`], every generated type containing them and collections of these,
+/// serializing them on their own, e.g. in a response or a list file, doesn't check them.
+pub(crate) trait Check {
+    /// Returns why the value can't be sent, if it can't.
+    fn check(&self) -> Result<(), String>;
+}
+
+impl Check for Message {
+    fn check(&self) -> Result<(), String> {
+        self.validate().map_err(|e| e.to_string())
+    }
+}
+
+impl Check for Player {
+    fn check(&self) -> Result<(), String> {
+        self.validate().map_err(|e| e.to_string())
+    }
+}
+
+impl<T: Check> Check for Option<T> {
+    fn check(&self) -> Result<(), String> {
+        self.as_ref().map_or(Ok(()), T::check)
+    }
+}
+
+impl<T: Check> Check for Vec<T> {
+    fn check(&self) -> Result<(), String> {
+        self.iter().try_for_each(T::check)
+    }
+}
+
+/// Checks `param`, then serializes it like [`serde_json::to_value`], failing with the reason it can't be sent.
+pub(crate) fn to_value<T: Check + Serialize>(param: T) -> serde_json::Result<Value> {
+    param.check().map_err(serde_json::Error::custom)?;
+    serde_json::to_value(param)
+}
@@ -0,0 +1,416 @@
+use std::fmt::{self, Display};
+
+use pale::PaleError;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// How strictly `Client::negotiate` treats a server whose schema differs from the compiled one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Negotiation {
+    /// Always connect and leave it to the caller to act on the [`CompatibilityReport`].
+    #[default]
+    Lenient,
+    /// Refuse to connect unless the report [is compatible](CompatibilityReport::is_compatible).
+    Strict,
+}
+
+/// The error returned by `Client::negotiate`.
+#[derive(Debug, Error)]
+pub enum NegotiationError {
+    #[error(transparent)]
+    Pale(#[from] PaleError),
+    /// The server was rejected in [`Negotiation::Strict`] mode, the connection is closed again.
+    #[error("Incompatible server: {0}")]
+    Incompatible(Box<CompatibilityReport>),
+}
+
+/// The differences between the schema a client was compiled with and the one a server reports from `rpc.discover`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// `info.version` of the compiled schema.
+    pub compiled_version: String,
+    /// `info.version` the server reported.
+    pub server_version: String,
+    /// Compiled methods the server doesn't have, calling these will fail.
+    pub missing_methods: Vec<String>,
+    /// Server methods the compiled bindings don't know about.
+    pub extra_methods: Vec<String>,
+    /// Methods both have, but with params or a result of a different shape.
+    pub changed_methods: Vec<ChangedMethod>,
+    /// Compiled `#/components/schemas` types the server doesn't have, or has in a different shape.
+    pub changed_types: Vec<String>,
+}
+
+/// A method whose params or result differ between the compiled and the server schema.
+///
+/// Shapes are compared with every `$ref` resolved and descriptions left out,
+/// so a change to a shared component shows up in every method using it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedMethod {
+    pub method: String,
+    /// The `(compiled, server)` params, if they differ.
+    pub params: Option<(Value, Value)>,
+    /// The `(compiled, server)` result, if it differs.
+    pub result: Option<(Value, Value)>,
+}
+
+impl CompatibilityReport {
+    /// Compares the `compiled` schema with the `server` one, both as returned by `rpc.discover`.
+    pub fn new(compiled: &Value, server: &Value) -> Self {
+        let compiled_methods = methods(compiled);
+        let server_methods = methods(server);
+
+        let missing_methods = compiled_methods
+            .iter()
+            .filter(|(name, _)| !server_methods.iter().any(|(n, _)| n == name))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let extra_methods = server_methods
+            .iter()
+            .filter(|(name, _)| !compiled_methods.iter().any(|(n, _)| n == name))
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        let changed_methods = compiled_methods
+            .iter()
+            .filter_map(|(name, compiled_method)| {
+                let (_, server_method) = server_methods.iter().find(|(n, _)| n == name)?;
+                let differs = |key: &str| {
+                    let compiled_shape = shape(compiled, compiled_method.get(key));
+                    let server_shape = shape(server, server_method.get(key));
+                    (compiled_shape != server_shape).then_some((compiled_shape, server_shape))
+                };
+
+                let params = differs("params");
+                let result = differs("result");
+                (params.is_some() || result.is_some()).then(|| ChangedMethod {
+                    method: name.to_string(),
+                    params,
+                    result,
+                })
+            })
+            .collect();
+
+        let server_types = types(server);
+        let changed_types = types(compiled)
+            .into_iter()
+            .filter(|(name, compiled_type)| {
+                let server_type = server_types.iter().find(|(n, _)| n == name);
+                server_type.is_none_or(|(_, server_type)| {
+                    shape(compiled, Some(compiled_type)) != shape(server, Some(server_type))
+                })
+            })
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        Self {
+            compiled_version: version(compiled),
+            server_version: version(server),
+            missing_methods,
+            extra_methods,
+            changed_methods,
+            changed_types,
+        }
+    }
+
+    /// Whether the server has every compiled method and type, unchanged.
+    ///
+    /// Versions aren't compared, a server on another version with the same methods and types works just the same.
+    /// Extra methods don't matter since the bindings never call them.
+    pub fn is_compatible(&self) -> bool {
+        self.missing_methods.is_empty()
+            && self.changed_methods.is_empty()
+            && self.changed_types.is_empty()
+    }
+}
+
+impl Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "compiled for {}, server runs {}, {} missing, {} extra and {} changed methods, {} changed types",
+            self.compiled_version,
+            self.server_version,
+            self.missing_methods.len(),
+            self.extra_methods.len(),
+            self.changed_methods.len(),
+            self.changed_types.len()
+        )
+    }
+}
+
+fn version(schema: &Value) -> String {
+    schema
+        .pointer("/info/version")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn methods(schema: &Value) -> Vec<(&str, &Value)> {
+    schema
+        .get("methods")
+        .and_then(|m| m.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|m| Some((m.get("name")?.as_str()?, m)))
+        .collect()
+}
+
+fn types(schema: &Value) -> Vec<(&str, &Value)> {
+    schema
+        .pointer("/components/schemas")
+        .and_then(|s| s.as_object())
+        .into_iter()
+        .flatten()
+        .map(|(name, schema)| (name.as_str(), schema))
+        .collect()
+}
+
+/// Returns `value` with every `$ref` into the components of `schema` resolved and every `description` annotation removed.
+fn shape(schema: &Value, value: Option<&Value>) -> Value {
+    let mut resolving = vec![];
+    value
+        .map(|value| resolve(schema, value, &mut resolving))
+        .unwrap_or_default()
+}
+
+/// `resolving` holds the refs currently being resolved, a ref to one of them is kept as is instead of recursing forever.
+///
+/// The keys of a `properties` object are property names, so a property called `description` is kept.
+fn resolve(schema: &Value, value: &Value, resolving: &mut Vec<String>) -> Value {
+    match value {
+        Value::Object(object) => {
+            if let Some(reference) = object.get("$ref").and_then(|r| r.as_str()) {
+                let target = reference
+                    .strip_prefix('#')
+                    .and_then(|pointer| schema.pointer(pointer));
+                if let Some(target) = target
+                    && !resolving.iter().any(|r| r == reference)
+                {
+                    resolving.push(reference.to_string());
+                    let resolved = resolve(schema, target, resolving);
+                    resolving.pop();
+                    return resolved;
+                }
+            }
+
+            Value::Object(
+                object
+                    .iter()
+                    .filter(|(key, _)| *key != "description")
+                    .map(|(key, value)| match value {
+                        Value::Object(properties) if key == "properties" => (
+                            key.clone(),
+                            Value::Object(
+                                properties
+                                    .iter()
+                                    .map(|(name, value)| {
+                                        (name.clone(), resolve(schema, value, resolving))
+                                    })
+                                    .collect(),
+                            ),
+                        ),
+                        value => (key.clone(), resolve(schema, value, resolving)),
+                    })
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| resolve(schema, value, resolving))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema(version: &str, methods: Value, player: Value) -> Value {
+        json!({
+            "info": { "title": "Minecraft Server JSON-RPC", "version": version },
+            "methods": methods,
+            "components": { "schemas": { "player": player } },
+        })
+    }
+
+    fn player() -> Value {
+        json!({
+            "type": "object",
+            "properties": { "id": { "type": "string" }, "name": { "type": "string" } },
+        })
+    }
+
+    fn method(name: &str, params: Value) -> Value {
+        json!({
+            "name": name,
+            "description": format!("Calls {name}"),
+            "params": params,
+            "result": { "name": "players", "schema": { "type": "array", "items": { "$ref": "#/components/schemas/player" } } },
+        })
+    }
+
+    fn add_params() -> Value {
+        json!([{ "name": "add", "schema": { "type": "array", "items": { "$ref": "#/components/schemas/player" } } }])
+    }
+
+    #[test]
+    fn same_schema_is_compatible() {
+        let compiled = schema(
+            "2.0.0",
+            json!([method("minecraft:allowlist/add", add_params())]),
+            player(),
+        );
+
+        let report = CompatibilityReport::new(&compiled, &compiled);
+        assert!(report.is_compatible());
+        assert!(report.missing_methods.is_empty());
+        assert!(report.extra_methods.is_empty());
+        assert!(report.changed_methods.is_empty());
+        assert!(report.changed_types.is_empty());
+    }
+
+    #[test]
+    fn missing_methods_are_incompatible() {
+        let compiled = schema(
+            "2.0.0",
+            json!([
+                method("minecraft:allowlist", json!([])),
+                method("minecraft:allowlist/add", add_params()),
+            ]),
+            player(),
+        );
+        let server = schema(
+            "2.0.0",
+            json!([method("minecraft:allowlist", json!([]))]),
+            player(),
+        );
+
+        let report = CompatibilityReport::new(&compiled, &server);
+        assert_eq!(report.missing_methods, ["minecraft:allowlist/add"]);
+        assert!(!report.is_compatible());
+    }
+
+    #[test]
+    fn extra_methods_are_compatible() {
+        let compiled = schema(
+            "2.0.0",
+            json!([method("minecraft:allowlist", json!([]))]),
+            player(),
+        );
+        let server = schema(
+            "2.0.0",
+            json!([
+                method("minecraft:allowlist", json!([])),
+                method("minecraft:allowlist/add", add_params()),
+            ]),
+            player(),
+        );
+
+        let report = CompatibilityReport::new(&compiled, &server);
+        assert_eq!(report.extra_methods, ["minecraft:allowlist/add"]);
+        assert!(report.is_compatible());
+    }
+
+    #[test]
+    fn changed_params_are_incompatible() {
+        let compiled = schema(
+            "2.0.0",
+            json!([method("minecraft:allowlist/add", add_params())]),
+            player(),
+        );
+        let changed = json!([{ "name": "add", "schema": { "type": "string" } }]);
+        let server = schema(
+            "2.0.0",
+            json!([method("minecraft:allowlist/add", changed.clone())]),
+            player(),
+        );
+
+        let report = CompatibilityReport::new(&compiled, &server);
+        let [changed_method] = report.changed_methods.as_slice() else {
+            panic!(
+                "expected one changed method, got {:?}",
+                report.changed_methods
+            );
+        };
+        assert_eq!(changed_method.method, "minecraft:allowlist/add");
+        let (_, server_params) = changed_method.params.as_ref().unwrap();
+        assert_eq!(server_params, &changed);
+        assert!(changed_method.result.is_none());
+        assert!(!report.is_compatible());
+    }
+
+    #[test]
+    fn changed_types_change_the_methods_using_them() {
+        let methods = json!([method("minecraft:allowlist/add", add_params())]);
+        let compiled = schema("2.0.0", methods.clone(), player());
+        let server_player =
+            json!({ "type": "object", "properties": { "id": { "type": "integer" } } });
+        let server = schema("2.0.0", methods, server_player);
+
+        let report = CompatibilityReport::new(&compiled, &server);
+        assert_eq!(report.changed_types, ["player"]);
+        assert_eq!(report.changed_methods.len(), 1);
+        assert!(report.changed_methods[0].params.is_some());
+        assert!(report.changed_methods[0].result.is_some());
+        assert!(!report.is_compatible());
+    }
+
+    #[test]
+    fn descriptions_are_ignored() {
+        let compiled = schema(
+            "2.0.0",
+            json!([method("minecraft:allowlist", json!([]))]),
+            player(),
+        );
+        let mut server = compiled.clone();
+        server["methods"][0]["description"] = json!("Something else");
+        server["components"]["schemas"]["player"]["description"] = json!("A player");
+
+        assert!(CompatibilityReport::new(&compiled, &server).is_compatible());
+    }
+
+    #[test]
+    fn properties_named_description_are_compared() {
+        let methods = json!([method("minecraft:allowlist/add", add_params())]);
+        let mut compiled_player = player();
+        compiled_player["properties"]["description"] =
+            json!({ "type": "string", "description": "What the player is like" });
+        let compiled = schema("2.0.0", methods.clone(), compiled_player.clone());
+
+        let mut described = compiled.clone();
+        described["components"]["schemas"]["player"]["properties"]["description"]["description"] =
+            json!("Something else");
+        assert!(CompatibilityReport::new(&compiled, &described).is_compatible());
+
+        let server = schema("2.0.0", methods.clone(), player());
+        let report = CompatibilityReport::new(&compiled, &server);
+        assert_eq!(report.changed_types, ["player"]);
+        assert!(!report.is_compatible());
+
+        compiled_player["properties"]["description"] = json!({ "type": "integer" });
+        let server = schema("2.0.0", methods, compiled_player);
+        assert_eq!(
+            CompatibilityReport::new(&compiled, &server).changed_types,
+            ["player"]
+        );
+    }
+
+    #[test]
+    fn version_mismatch_alone_is_compatible() {
+        let methods = json!([method("minecraft:allowlist/add", add_params())]);
+        let compiled = schema("2.0.0", methods.clone(), player());
+        let server = schema("2.1.0", methods, player());
+
+        let report = CompatibilityReport::new(&compiled, &server);
+        assert_eq!(report.compiled_version, "2.0.0");
+        assert_eq!(report.server_version, "2.1.0");
+        assert!(report.is_compatible());
+    }
+}
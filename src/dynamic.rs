@@ -0,0 +1,349 @@
+use std::{collections::HashMap, sync::Arc};
+
+use pale::PaleError;
+use serde_json::Value;
+use thiserror::Error;
+use tokio_stream::Stream;
+
+use crate::{
+    notification::{NotificationError, Notifications, decode_stream},
+    request::Requests,
+};
+
+/// The error returned by [`DynamicClient`] methods.
+#[derive(Debug, Error)]
+pub enum DynamicError {
+    /// The schema of the [`DynamicClient`] doesn't describe this method.
+    #[error("Unknown method {0}")]
+    UnknownMethod(String),
+    /// The params don't match what the schema describes, nothing was sent to the server.
+    #[error("Invalid params for {method}: {message}")]
+    InvalidParams { method: String, message: String },
+    #[error(transparent)]
+    Pale(Box<PaleError>),
+}
+
+impl From<PaleError> for DynamicError {
+    fn from(error: PaleError) -> Self {
+        Self::Pale(Box::new(error))
+    }
+}
+
+/// Calls methods described by an OpenRPC document loaded at runtime, for methods the generated bindings don't know yet.
+///
+/// Created from a connected `Client` with `Client::dynamic` or `Client::discover`, and uses the same connection and notification subscriptions.
+/// Params are validated against the schema before they are sent.
+///
+/// ## Example
+/// ```no_run
+/// # use serde_json::json;
+/// # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {
+/// // every method the server reports from `rpc.discover`
+/// let dynamic = client.discover().await?;
+/// let players = dynamic.call("minecraft:players", json!(null)).await?;
+///
+/// // or every method of a schema on disk
+/// let schema = serde_json::from_str(&std::fs::read_to_string("schemas/2.0.0.json")?)?;
+/// let dynamic = client.dynamic(schema);
+/// dynamic.call("minecraft:serversettings/motd/set", json!({ "message": "Hello" })).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicClient {
+    client: pale::Client,
+    notifications: Notifications,
+    requests: Requests,
+    schema: Arc<Value>,
+}
+
+impl DynamicClient {
+    pub(crate) fn new(
+        client: pale::Client,
+        notifications: Notifications,
+        requests: Requests,
+        schema: Value,
+    ) -> Self {
+        Self {
+            client,
+            notifications,
+            requests,
+            schema: Arc::new(schema),
+        }
+    }
+
+    /// The OpenRPC document methods are looked up in.
+    pub fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    /// The name of every method in the schema, notifications included.
+    pub fn methods(&self) -> impl Iterator<Item = &str> {
+        self.schema
+            .get("methods")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m.get("name")?.as_str())
+    }
+
+    /// Checks `params`, either named, `{"name": param}`, positional, `[param]`, or `null` for none,
+    /// against the schema of `method` without sending anything.
+    pub fn validate(&self, method: &str, params: &Value) -> Result<(), DynamicError> {
+        self.named_params(method, params).map(|_| ())
+    }
+
+    /// Validates `params` like [`validate`](Self::validate), then calls `method` and returns its raw result.
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, DynamicError> {
+        let params = self.named_params(method, &params)?;
+        let params = (!params.is_empty()).then_some(params);
+
+        Ok(self.requests.send(&self.client, method, params).await?)
+    }
+
+    /// Returns a [`Stream`] of the raw `params` of every `method` notification, `null` for notifications without params.
+    ///
+    /// Like the streams of the `Client`, it shares a single subscription per method with every other stream on the connection,
+    /// and yields [`NotificationError::Lagged`] for the notifications it missed if it falls behind.
+    pub async fn subscribe(
+        &self,
+        method: &str,
+    ) -> Result<impl Stream<Item = Result<Value, NotificationError>> + use<>, DynamicError> {
+        self.method(method)?;
+        let receiver = self
+            .notifications
+            .subscribe(&self.client, &[method])
+            .await?;
+
+        let method = method.to_string();
+        Ok(decode_stream(receiver, move |m, params| {
+            (m == method).then_some(Ok(params))
+        }))
+    }
+
+    fn method(&self, method: &str) -> Result<&Value, DynamicError> {
+        self.schema
+            .get("methods")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .find(|m| m.get("name").and_then(|n| n.as_str()) == Some(method))
+            .ok_or_else(|| DynamicError::UnknownMethod(method.to_string()))
+    }
+
+    /// Validates `params` and returns them by name, the only way the connection can send them.
+    fn named_params(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Result<HashMap<String, Value>, DynamicError> {
+        let descriptors = self
+            .method(method)?
+            .get("params")
+            .and_then(|p| p.as_array())
+            .map(|p| p.as_slice())
+            .unwrap_or_default();
+        let invalid = |message: String| DynamicError::InvalidParams {
+            method: method.to_string(),
+            message,
+        };
+
+        let mut named = HashMap::new();
+        for (i, descriptor) in descriptors.iter().enumerate() {
+            let Some(name) = descriptor.get("name").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            let param = match params {
+                Value::Object(params) => params.get(name),
+                Value::Array(params) => params.get(i),
+                _ => None,
+            };
+
+            match param {
+                Some(param) => {
+                    if let Some(schema) = descriptor.get("schema") {
+                        validate(&self.schema, schema, param, name).map_err(invalid)?;
+                    }
+                    named.insert(name.to_string(), param.clone());
+                }
+                None if descriptor.get("required").and_then(|r| r.as_bool()) == Some(true) => {
+                    return Err(invalid(format!("missing param '{name}'")));
+                }
+                None => (),
+            }
+        }
+
+        let known = |name: &str| {
+            descriptors
+                .iter()
+                .any(|d| d.get("name").and_then(|n| n.as_str()) == Some(name))
+        };
+        match params {
+            Value::Object(params) => {
+                if let Some(name) = params.keys().find(|name| !known(name)) {
+                    return Err(invalid(format!("unknown param '{name}'")));
+                }
+            }
+            Value::Array(params) if params.len() > descriptors.len() => {
+                return Err(invalid(format!(
+                    "expected at most {} params, got {}",
+                    descriptors.len(),
+                    params.len()
+                )));
+            }
+            Value::Array(_) | Value::Null => (),
+            _ => {
+                return Err(invalid(
+                    "params must be an object, an array or null".to_string(),
+                ));
+            }
+        }
+
+        Ok(named)
+    }
+}
+
+/// Validates `value` against the subset of JSON Schema the protocol uses, `path` is where `value` is for error messages.
+fn validate(document: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+        let Some(target) = reference
+            .strip_prefix('#')
+            .and_then(|pointer| document.pointer(pointer))
+        else {
+            return Err(format!("{path}: unresolvable $ref '{reference}'"));
+        };
+        return validate(document, target, value, path);
+    }
+
+    if let Some(variants) = schema.get("enum").and_then(|e| e.as_array())
+        && !variants.contains(value)
+    {
+        return Err(format!(
+            "{path}: {value} is not one of {}",
+            Value::Array(variants.clone())
+        ));
+    }
+
+    let types = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+        _ => vec![],
+    };
+    if !types.is_empty() && !types.iter().any(|t| is_type(value, t)) {
+        return Err(format!(
+            "{path}: expected {}, got {value}",
+            types.join(" or ")
+        ));
+    }
+
+    match value {
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(document, item_schema, item, &format!("{path}[{i}]"))?;
+                }
+            }
+        }
+        Value::Object(fields) => {
+            if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+                for (name, field) in fields {
+                    if let Some(field_schema) = properties.get(name) {
+                        validate(document, field_schema, field, &format!("{path}.{name}"))?;
+                    }
+                }
+            }
+            let required = schema.get("required").and_then(|r| r.as_array());
+            for name in required.into_iter().flatten().filter_map(|n| n.as_str()) {
+                if !fields.contains_key(name) {
+                    return Err(format!("{path}: missing field '{name}'"));
+                }
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+fn is_type(value: &Value, schema_type: &str) -> bool {
+    match schema_type {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        // unknown types are left to the server
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document() -> Value {
+        json!({
+            "components": {
+                "schemas": {
+                    "difficulty": { "type": "string", "enum": ["peaceful", "easy", "normal", "hard"] },
+                    "player": {
+                        "type": "object",
+                        "properties": { "id": { "type": "string" }, "name": { "type": "string" } },
+                        "required": ["name"],
+                    },
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn refs_are_resolved() {
+        let schema = json!({ "type": "array", "items": { "$ref": "#/components/schemas/player" } });
+
+        assert!(validate(&document(), &schema, &json!([{ "name": "Steve" }]), "add").is_ok());
+        let error = validate(&document(), &schema, &json!([{ "name": 1 }]), "add").unwrap_err();
+        assert_eq!(error, "add[0].name: expected string, got 1");
+    }
+
+    #[test]
+    fn unresolvable_refs_fail() {
+        let schema = json!({ "$ref": "#/components/schemas/nothing" });
+
+        let error = validate(&document(), &schema, &json!("x"), "param").unwrap_err();
+        assert_eq!(
+            error,
+            "param: unresolvable $ref '#/components/schemas/nothing'"
+        );
+    }
+
+    #[test]
+    fn enums_only_accept_their_variants() {
+        let schema = json!({ "$ref": "#/components/schemas/difficulty" });
+
+        assert!(validate(&document(), &schema, &json!("hard"), "difficulty").is_ok());
+        let error = validate(&document(), &schema, &json!("insane"), "difficulty").unwrap_err();
+        assert!(error.starts_with("difficulty: \"insane\" is not one of"));
+    }
+
+    #[test]
+    fn required_fields_must_be_present() {
+        let schema = json!({ "$ref": "#/components/schemas/player" });
+
+        assert!(validate(&document(), &schema, &json!({ "name": "Steve" }), "player").is_ok());
+        let error = validate(&document(), &schema, &json!({ "id": "x" }), "player").unwrap_err();
+        assert_eq!(error, "player: missing field 'name'");
+    }
+
+    #[test]
+    fn types_may_be_a_list() {
+        let schema = json!({ "type": ["integer", "null"] });
+
+        assert!(validate(&document(), &schema, &json!(1), "value").is_ok());
+        assert!(validate(&document(), &schema, &json!(null), "value").is_ok());
+        assert!(validate(&document(), &schema, &json!(1.5), "value").is_err());
+    }
+}
@@ -0,0 +1,546 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use thiserror::Error;
+
+use crate::{IncomingIpBan, IpBan, Player, UserBan};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// When a ban ends, the `expires` of [`UserBan`], [`IpBan`] and [`IncomingIpBan`].
+///
+/// Serialized as `forever` or a UTC timestamp like `2025-10-18T12:00:00Z`.
+/// Timestamps with an offset, or in the ban list format `2025-10-18 14:00:00 +0200`, are read as well.
+///
+/// ## Example
+/// ```
+/// use mc_rpc::Expiry;
+///
+/// let expiry: Expiry = "2025-10-18 14:00:00 +0200".parse()?;
+/// assert_eq!(expiry.to_string(), "2025-10-18T12:00:00Z");
+/// assert_eq!("forever".parse::<Expiry>()?, Expiry::Permanent);
+/// # Ok::<(), mc_rpc::ParseExpiryError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Expiry {
+    At(SystemTime),
+    /// Never, comes after every timestamp.
+    Permanent,
+}
+
+/// The error returned when a string isn't an [`Expiry`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid expiry '{0}'")]
+pub struct ParseExpiryError(String);
+
+impl Expiry {
+    /// The sentinel the server uses for bans that never end.
+    pub const FOREVER: &str = "forever";
+
+    /// An expiry `duration` from now, [`Permanent`](Self::Permanent) if that's too far in the future to represent.
+    pub fn after(duration: Duration) -> Self {
+        SystemTime::now()
+            .checked_add(duration)
+            .map_or(Self::Permanent, Self::At)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+
+    /// The time left until the expiry, zero once it has passed, or `None` if it is permanent.
+    pub fn remaining(&self) -> Option<Duration> {
+        match self {
+            Self::At(time) => Some(
+                time.duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO),
+            ),
+            Self::Permanent => None,
+        }
+    }
+
+    /// The expiry in the format of the server's ban list files, e.g. `2025-10-18 12:00:00 +0000`.
+    pub(crate) fn to_ban_list(self) -> String {
+        match self {
+            Self::At(time) => ban_list_time(time),
+            Self::Permanent => Self::FOREVER.to_string(),
+        }
+    }
+}
+
+impl From<SystemTime> for Expiry {
+    fn from(time: SystemTime) -> Self {
+        Self::At(time)
+    }
+}
+
+impl Display for Expiry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::At(time) => {
+                let (year, month, day, [hour, minute, second]) = utc(*time);
+                write!(
+                    f,
+                    "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z"
+                )
+            }
+            Self::Permanent => f.write_str(Self::FOREVER),
+        }
+    }
+}
+
+/// `time` in the format of the server's ban list files, in UTC.
+pub(crate) fn ban_list_time(time: SystemTime) -> String {
+    let (year, month, day, [hour, minute, second]) = utc(time);
+    format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02} +0000")
+}
+
+/// The UTC date and time of day of `time`, in whole seconds.
+fn utc(time: SystemTime) -> (i64, u32, u32, [i64; 3]) {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+    };
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let time_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+    (
+        year,
+        month,
+        day,
+        [
+            time_of_day / 3600,
+            time_of_day % 3600 / 60,
+            time_of_day % 60,
+        ],
+    )
+}
+
+impl FromStr for Expiry {
+    type Err = ParseExpiryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case(Self::FOREVER) {
+            return Ok(Self::Permanent);
+        }
+        parse_timestamp(s.trim())
+            .map(Self::At)
+            .ok_or_else(|| ParseExpiryError(s.to_string()))
+    }
+}
+
+impl Serialize for Expiry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expiry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Parses `2025-10-18T12:00:00Z`, with optional fractional seconds and any offset, or `2025-10-18 14:00:00 +0200`.
+fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let (date, rest) = s.split_at_checked(10)?;
+    let mut date = date.split('-');
+    let year = digits(date.next()?, 4)?;
+    let month = digits(date.next()?, 2)? as u32;
+    let day = digits(date.next()?, 2)? as u32;
+
+    let rest = rest.strip_prefix(['T', 't', ' '])?;
+    let offset_start = rest.find(['Z', 'z', '+', '-'])?;
+    let (time, offset) = rest.split_at(offset_start);
+
+    let (time, fraction) = match time.trim_end().split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (time.trim_end(), None),
+    };
+    let mut time = time.split(':');
+    let hour = digits(time.next()?, 2)?;
+    let minute = digits(time.next()?, 2)?;
+    let second = digits(time.next()?, 2)?;
+    if time.next().is_some() {
+        return None;
+    }
+    let nanos = match fraction {
+        None => 0,
+        Some("") => return None,
+        Some(fraction) => digits(format!("{fraction:0<9}").get(..9)?, 9)?,
+    };
+
+    // `Z`, or a sign followed by `HH:MM` or `HHMM`
+    let offset = match offset {
+        "Z" | "z" => 0,
+        offset => {
+            let (sign, hours_minutes) = match offset.split_at_checked(1)? {
+                ("+", rest) => (1, rest),
+                ("-", rest) => (-1, rest),
+                _ => return None,
+            };
+            let (hours, minutes) = match hours_minutes.len() {
+                4 => hours_minutes.split_at_checked(2)?,
+                5 => {
+                    let (hours, minutes) = hours_minutes.split_at_checked(2)?;
+                    (hours, minutes.strip_prefix(':')?)
+                }
+                _ => return None,
+            };
+            sign * (digits(hours, 2)? * 3600 + digits(minutes, 2)? * 60)
+        }
+    };
+
+    let days = days_from_civil(year, month, day);
+    // a day past the end of its month, like 2023-02-29, comes back as another date
+    let valid = (1..=12).contains(&month)
+        && (1..=31).contains(&day)
+        && civil_from_days(days) == (year, month, day)
+        && (0..24).contains(&hour)
+        && (0..60).contains(&minute)
+        && (0..=60).contains(&second);
+    if !valid {
+        return None;
+    }
+
+    let seconds = days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second - offset;
+    let time = if seconds >= 0 {
+        UNIX_EPOCH + Duration::from_secs(seconds as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+    };
+    Some(time + Duration::from_nanos(nanos as u64))
+}
+
+/// The number written with exactly `len` ASCII digits in `s`, no sign or whitespace.
+fn digits(s: &str, len: usize) -> Option<i64> {
+    if s.len() != len || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The date of a number of days since 1970-01-01, the inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl UserBan {
+    /// A ban of `player` that ends `duration` from now.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use mc_rpc::{Player, UserBan};
+    /// # async fn example(client: mc_rpc::Client, player: Player) -> mc_rpc::Result<()> {
+    /// let ban = UserBan::temporary(player, Duration::from_secs(24 * 60 * 60), "Cool down for a day");
+    /// client.bans().add(vec![ban]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn temporary(player: Player, duration: Duration, reason: impl Into<String>) -> Self {
+        Self {
+            expires: Some(Expiry::after(duration)),
+            player,
+            reason: Some(reason.into()),
+            source: None,
+        }
+    }
+
+    /// A ban of `player` that never ends, expiring [`Expiry::Permanent`] like a permanent ban read back from the server.
+    pub fn permanent(player: Player, reason: impl Into<String>) -> Self {
+        Self {
+            expires: Some(Expiry::Permanent),
+            player,
+            reason: Some(reason.into()),
+            source: None,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires.is_expired())
+    }
+}
+
+impl IpBan {
+    /// A ban of `ip` that ends `duration` from now.
+    pub fn temporary(ip: impl Into<String>, duration: Duration, reason: impl Into<String>) -> Self {
+        Self {
+            expires: Some(Expiry::after(duration)),
+            ip: ip.into(),
+            reason: Some(reason.into()),
+            source: None,
+        }
+    }
+
+    /// A ban of `ip` that never ends, expiring [`Expiry::Permanent`] like a permanent ban read back from the server.
+    pub fn permanent(ip: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            expires: Some(Expiry::Permanent),
+            ip: ip.into(),
+            reason: Some(reason.into()),
+            source: None,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires.is_expired())
+    }
+}
+
+impl IncomingIpBan {
+    /// A ban of `ip` that ends `duration` from now.
+    pub fn temporary(ip: impl Into<String>, duration: Duration, reason: impl Into<String>) -> Self {
+        Self {
+            expires: Some(Expiry::after(duration)),
+            ip: Some(ip.into()),
+            reason: Some(reason.into()),
+            ..Default::default()
+        }
+    }
+
+    /// A ban of `ip` that never ends, expiring [`Expiry::Permanent`] like a permanent ban read back from the server.
+    pub fn permanent(ip: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            expires: Some(Expiry::Permanent),
+            ip: Some(ip.into()),
+            reason: Some(reason.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> Expiry {
+        Expiry::At(if seconds >= 0 {
+            UNIX_EPOCH + Duration::from_secs(seconds as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+        })
+    }
+
+    fn parse(s: &str) -> Expiry {
+        s.parse().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(parse("2024-02-29T00:00:00Z"), at(1_709_164_800));
+        assert_eq!(parse("2000-02-29T00:00:00Z"), at(951_782_400));
+        // 1900 isn't a leap year, the day after february 28 is march 1
+        assert_eq!(parse("1900-03-01T00:00:00Z"), at(-2_203_891_200));
+        assert_eq!(
+            parse("1900-02-28T00:00:00Z"),
+            at(-2_203_891_200 - SECONDS_PER_DAY)
+        );
+
+        assert!("1900-02-29T00:00:00Z".parse::<Expiry>().is_err());
+        assert!("2023-02-29T00:00:00Z".parse::<Expiry>().is_err());
+        assert!("2024-04-31T00:00:00Z".parse::<Expiry>().is_err());
+    }
+
+    #[test]
+    fn before_1970() {
+        assert_eq!(parse("1969-12-31T23:59:59Z"), at(-1));
+        assert_eq!(parse("1969-07-20 20:17:40 +0000"), at(-14_182_940));
+        assert_eq!(at(-1).to_string(), "1969-12-31T23:59:59Z");
+        assert_eq!(at(-14_182_940).to_string(), "1969-07-20T20:17:40Z");
+        // fractions of a second before 1970 still belong to the second they started in
+        let before = Expiry::At(UNIX_EPOCH - Duration::from_millis(500));
+        assert_eq!(before.to_string(), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn offsets() {
+        let utc = parse("2025-10-18T12:00:00Z");
+        assert_eq!(parse("2025-10-18 13:30:00 +0130"), utc);
+        assert_eq!(parse("2025-10-18T13:30:00+01:30"), utc);
+        assert_eq!(parse("2025-10-18 07:00:00 -0500"), utc);
+        assert_eq!(parse("2025-10-18T07:00:00-05:00"), utc);
+        // an offset can move the date
+        assert_eq!(
+            parse("2025-10-17 23:00:00 -0500"),
+            parse("2025-10-18T04:00:00Z")
+        );
+        assert_eq!(
+            parse("2025-10-18 00:30:00 +0130"),
+            parse("2025-10-17T23:00:00Z")
+        );
+    }
+
+    #[test]
+    fn forever() {
+        assert_eq!(parse("forever"), Expiry::Permanent);
+        assert_eq!(parse(" FOREVER "), Expiry::Permanent);
+        assert_eq!(Expiry::Permanent.to_string(), "forever");
+        assert_eq!(Expiry::Permanent.to_ban_list(), "forever");
+        assert!(Expiry::Permanent > parse("9999-12-31T23:59:59Z"));
+        assert_eq!(Expiry::Permanent.remaining(), None);
+        assert!(!Expiry::Permanent.is_expired());
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        for input in [
+            "",
+            "never",
+            "2025",
+            "2025-10-18",
+            "2025-10-18T12:00:00",
+            "2025-13-01T00:00:00Z",
+            "2025-10-00T00:00:00Z",
+            "2025-10-18T24:00:00Z",
+            "2025-10-18T12:60:00Z",
+            "2025-10-18T12:00Z",
+            "2025-10-18T12:00:00+2",
+            "2025-10-18T12:00:00+02:00:00",
+            "2025-10-18T12:00:00+ab00",
+            "2025-10-18X12:00:00Z",
+            "2025-1ä-18T12:00:00Z",
+            "2025-10-18T12:00:00.ä1Z",
+            "äääää-10-18T12:00:00Z",
+            "-2025-10-18T12:00:00Z",
+            "2025-10-18T12:00:00Z1234",
+            "2025-10-18T12:00:00Z12:34",
+            "2025-10-18T12:00:00zZ",
+            "2025-10-18T12:00:00+1:234",
+            "2025-10-18T12:00:00+12:3:4",
+            "2025-10-18T12:00:00+-1-1",
+            "2025-10-18T12:00:00++0100",
+            "2025-10-18T12:00:00+01-00",
+            "2025-10-18T12:00:00+0١00",
+            "2025-10-18T12:00:00+0١0",
+            "2025-+1-18T12:00:00Z",
+            "2025-10-18T+1:00:00Z",
+            "2025-10-18T1:00:00Z",
+            "2025-10-18T12:00:00.Z",
+            "2025-10-18T12:00:00.+1Z",
+        ] {
+            assert_eq!(
+                input.parse::<Expiry>(),
+                Err(ParseExpiryError(input.to_string())),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fractional_seconds() {
+        let expiry = parse("2025-10-18T12:00:00.25Z");
+        assert_eq!(
+            expiry,
+            Expiry::At(UNIX_EPOCH + Duration::new(1_760_788_800, 250_000_000))
+        );
+        // displayed in whole seconds
+        assert_eq!(expiry.to_string(), "2025-10-18T12:00:00Z");
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for seconds in [
+            0,
+            -1,
+            951_782_400,
+            1_709_164_800,
+            -2_203_891_200,
+            1_760_788_800,
+            253_402_300_799,
+            -62_135_596_800,
+        ] {
+            let expiry = at(seconds);
+            assert_eq!(parse(&expiry.to_string()), expiry, "{expiry}");
+            assert_eq!(parse(&expiry.to_ban_list()), expiry, "{expiry}");
+        }
+
+        for input in ["2024-02-29T23:59:59Z", "1900-03-01T00:00:00Z", "forever"] {
+            assert_eq!(parse(input).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn far_future_expiries_are_permanent() {
+        assert_eq!(Expiry::after(Duration::MAX), Expiry::Permanent);
+        assert!(matches!(
+            Expiry::after(Duration::from_secs(60)),
+            Expiry::At(_)
+        ));
+    }
+
+    #[test]
+    fn permanent_bans_equal_the_bans_read_back() {
+        let ban = UserBan::permanent(Player::by_name("Steve"), "Griefing");
+        let read = serde_json::from_value::<UserBan>(serde_json::json!({
+            "player": { "name": "Steve" },
+            "reason": "Griefing",
+            "expires": "forever",
+        }))
+        .unwrap();
+        assert_eq!(ban, read);
+        assert_eq!(
+            serde_json::from_value::<UserBan>(serde_json::to_value(&ban).unwrap()).unwrap(),
+            ban
+        );
+
+        let ban = IpBan::permanent("10.0.0.1", "Spam");
+        let read = serde_json::from_value::<IpBan>(serde_json::json!({
+            "ip": "10.0.0.1",
+            "reason": "Spam",
+            "expires": "forever",
+        }))
+        .unwrap();
+        assert_eq!(ban, read);
+        assert_eq!(
+            IncomingIpBan::permanent("10.0.0.1", "Spam").expires,
+            Some(Expiry::Permanent)
+        );
+        assert!(!ban.is_expired());
+    }
+
+    #[test]
+    fn serde_uses_the_display_format() {
+        let expiry = parse("2025-10-18 14:00:00 +0200");
+        assert_eq!(
+            serde_json::to_value(expiry).unwrap(),
+            "2025-10-18T12:00:00Z"
+        );
+        assert_eq!(
+            serde_json::from_value::<Expiry>("forever".into()).unwrap(),
+            Expiry::Permanent
+        );
+        assert!(serde_json::from_value::<Expiry>("tomorrow".into()).is_err());
+    }
+}
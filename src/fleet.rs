@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use futures_util::{
+    Stream, StreamExt,
+    future::join_all,
+    stream::{BoxStream, select_all},
+};
+use pale::Result;
+
+/// The result of an operation on every selected server of a [`Fleet`], by server name.
+pub type FleetResults<T> = BTreeMap<String, Result<T>>;
+
+/// Many named clients, one per server, each in any number of groups.
+///
+/// Operations run on every server at once, through a [`FleetSelection`] of [`all`](Self::all) of them
+/// or of a [`group`](Self::group), and return a result per server.
+///
+/// ## Example
+/// ```no_run
+/// # use mc_rpc::{Client, ClientConfig, Fleet, Message, Player};
+/// # async fn example() -> mc_rpc::Result<()> {
+/// let mut fleet = Fleet::new();
+/// for (name, url) in [("lobby-1", "ws://10.0.0.1:25585"), ("lobby-2", "ws://10.0.0.2:25585")] {
+///     fleet.insert(name, Client::new(url, ClientConfig::default()).await?, ["lobby"]);
+/// }
+/// fleet.insert("survival", Client::new("ws://10.0.0.3:25585", ClientConfig::default()).await?, ["games"]);
+///
+/// let results = fleet.all().allowlist_add(vec![Player::by_name("Steve")]).await;
+/// for (server, result) in results {
+///     if let Err(e) = result {
+///         println!("{server}: {e}");
+///     }
+/// }
+/// let message = Message::literal("Restarting in 5 minutes");
+/// fleet.group("lobby").server_system_message(message.into()).await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fleet<C = crate::Client> {
+    servers: BTreeMap<String, Member<C>>,
+}
+
+#[derive(Debug, Clone)]
+struct Member<C> {
+    client: C,
+    groups: BTreeSet<String>,
+}
+
+impl<C> Default for Fleet<C> {
+    fn default() -> Self {
+        Self {
+            servers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<C> Fleet<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the client of the server `name` in `groups`, returning the client it replaces.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        client: C,
+        groups: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Option<C> {
+        let member = Member {
+            client,
+            groups: groups.into_iter().map(Into::into).collect(),
+        };
+        self.servers
+            .insert(name.into(), member)
+            .map(|member| member.client)
+    }
+
+    /// Removes the server `name`, returning its client.
+    pub fn remove(&mut self, name: &str) -> Option<C> {
+        self.servers.remove(name).map(|member| member.client)
+    }
+
+    /// The client of the server `name`.
+    pub fn get(&self, name: &str) -> Option<&C> {
+        self.servers.get(name).map(|member| &member.client)
+    }
+
+    /// The groups the server `name` is in.
+    pub fn groups(&self, name: &str) -> Option<&BTreeSet<String>> {
+        self.servers.get(name).map(|member| &member.groups)
+    }
+
+    /// The name of every server, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.servers.keys().map(String::as_str)
+    }
+
+    /// Every server and its client, in order of their names.
+    pub fn clients(&self) -> impl Iterator<Item = (&str, &C)> {
+        self.servers
+            .iter()
+            .map(|(name, member)| (name.as_str(), &member.client))
+    }
+
+    pub fn len(&self) -> usize {
+        self.servers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.servers.is_empty()
+    }
+
+    /// Selects every server.
+    pub fn all(&self) -> FleetSelection<'_, C> {
+        FleetSelection(self.clients().collect())
+    }
+
+    /// Selects the servers in `group`, none if there is no such group.
+    pub fn group(&self, group: &str) -> FleetSelection<'_, C> {
+        FleetSelection(
+            self.servers
+                .iter()
+                .filter(|(_, member)| member.groups.contains(group))
+                .map(|(name, member)| (name.as_str(), &member.client))
+                .collect(),
+        )
+    }
+}
+
+/// Some servers of a [`Fleet`], see [`Fleet::all`] and [`Fleet::group`].
+///
+/// Every request of the protocol is a method of its own, named like the methods of a `Batch`, e.g. `settings_motd_set`,
+/// which runs on every selected server at once and returns a result per server.
+#[derive(Debug, Clone)]
+pub struct FleetSelection<'a, C = crate::Client>(Vec<(&'a str, &'a C)>);
+
+impl<C: Clone> FleetSelection<'_, C> {
+    /// The name of every selected server, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(name, _)| *name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Calls `call` with the client of every selected server at once, returning the result of each by server name.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # async fn example(fleet: mc_rpc::Fleet, spec: mc_rpc::ServerSpec) {
+    /// let plans = fleet.all().fan_out(|client| {
+    ///     let spec = spec.clone();
+    ///     async move { client.plan(&spec).await }
+    /// }).await;
+    /// # }
+    /// ```
+    pub async fn fan_out<T, F, Fut>(&self, call: F) -> FleetResults<T>
+    where
+        F: Fn(C) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let calls = self.0.iter().map(|(name, client)| {
+            let call = call((*client).clone());
+            async move { (name.to_string(), call.await) }
+        });
+        join_all(calls).await.into_iter().collect()
+    }
+}
+
+/// Merges a stream of every server, each item tagged with the name of its server.
+pub(crate) fn merge<T, S>(streams: Vec<(String, S)>) -> impl Stream<Item = (String, T)> + use<T, S>
+where
+    T: Send + 'static,
+    S: Stream<Item = T> + Send + 'static,
+{
+    let streams = streams
+        .into_iter()
+        .map(|(name, stream)| stream.map(move |item| (name.clone(), item)).boxed())
+        .collect::<Vec<BoxStream<'static, (String, T)>>>();
+    select_all(streams)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use pale::PaleError;
+
+    use super::*;
+
+    /// A client of a server that only knows its players, failing every call if it's `down`.
+    #[derive(Debug, Clone)]
+    struct Stub {
+        players: usize,
+        down: bool,
+    }
+
+    impl Stub {
+        async fn players(self) -> Result<usize> {
+            match self.down {
+                true => Err(PaleError::ClientDisconnected),
+                false => Ok(self.players),
+            }
+        }
+    }
+
+    fn fleet() -> Fleet<Stub> {
+        let mut fleet = Fleet::new();
+        let stub = |players, down| Stub { players, down };
+        fleet.insert("lobby-1", stub(3, false), ["lobby"]);
+        fleet.insert("lobby-2", stub(0, true), ["lobby"]);
+        fleet.insert("survival", stub(12, false), ["games", "survival"]);
+        fleet
+    }
+
+    #[tokio::test]
+    async fn fan_out_returns_a_result_per_server() {
+        let results = fleet().all().fan_out(Stub::players).await;
+
+        assert_eq!(
+            results.keys().collect::<Vec<_>>(),
+            ["lobby-1", "lobby-2", "survival"]
+        );
+        assert_eq!(results["lobby-1"].as_ref().unwrap(), &3);
+        assert!(matches!(
+            results["lobby-2"],
+            Err(PaleError::ClientDisconnected)
+        ));
+        assert_eq!(results["survival"].as_ref().unwrap(), &12);
+    }
+
+    #[tokio::test]
+    async fn fan_out_only_runs_on_the_selection() {
+        let fleet = fleet();
+
+        let results = fleet.group("games").fan_out(Stub::players).await;
+        assert_eq!(results.keys().collect::<Vec<_>>(), ["survival"]);
+
+        let selection = fleet.group("creative");
+        assert!(selection.is_empty());
+        assert!(selection.fan_out(Stub::players).await.is_empty());
+    }
+
+    #[test]
+    fn replaced_servers_keep_one_entry() {
+        let mut fleet = fleet();
+        let replaced = fleet.insert(
+            "lobby-2",
+            Stub {
+                players: 1,
+                down: false,
+            },
+            ["games"],
+        );
+
+        assert!(replaced.is_some_and(|stub| stub.down));
+        assert_eq!(fleet.len(), 3);
+        assert_eq!(
+            fleet.group("lobby").names().collect::<Vec<_>>(),
+            ["lobby-1"]
+        );
+        assert_eq!(
+            fleet.group("games").names().collect::<Vec<_>>(),
+            ["lobby-2", "survival"]
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_tags_every_item_with_its_server() {
+        let merged = merge(vec![
+            ("lobby-1".to_string(), stream::iter([1, 2]).boxed()),
+            ("survival".to_string(), stream::iter([3]).boxed()),
+            ("lobby-2".to_string(), stream::empty().boxed()),
+        ]);
+        let mut items = merged.collect::<Vec<(String, i32)>>().await;
+        items.sort();
+
+        assert_eq!(
+            items,
+            [
+                ("lobby-1".to_string(), 1),
+                ("lobby-1".to_string(), 2),
+                ("survival".to_string(), 3),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_keeps_the_order_of_each_server() {
+        let merged = merge(vec![
+            ("a".to_string(), stream::iter(0..100)),
+            ("b".to_string(), stream::iter(100..200)),
+        ]);
+        let items = merged.collect::<Vec<(String, i32)>>().await;
+
+        for server in ["a", "b"] {
+            let of_server = items
+                .iter()
+                .filter(|(name, _)| name == server)
+                .map(|(_, item)| *item)
+                .collect::<Vec<i32>>();
+            assert_eq!(of_server.len(), 100);
+            assert!(of_server.is_sorted());
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use std::{collections::BTreeMap, result::Result as StdResult};
+
+use pale::Result;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+
+use crate::{
+    Client, TypedGameRule, TypedGameRuleType, TypedGameRuleValue, UntypedGameRule,
+    UntypedGameRuleValue,
+};
+
+/// A game rule with a known key and value type, see [`Client::gamerule`].
+pub trait GameRule {
+    /// The key the server knows the rule by, e.g. `keepInventory`.
+    const KEY: &'static str;
+    type Value: GameRuleValue;
+}
+
+/// The value of a game rule, either a `bool` or an `i32`.
+pub trait GameRuleValue: Sized {
+    /// The type the server reports rules with this value as.
+    const TYPE: TypedGameRuleType;
+
+    /// Returns the value if `value` is of this type.
+    fn from_typed(value: &TypedGameRuleValue) -> Option<Self>;
+    fn into_typed(self) -> TypedGameRuleValue;
+    fn into_untyped(self) -> UntypedGameRuleValue;
+}
+
+impl GameRuleValue for bool {
+    const TYPE: TypedGameRuleType = TypedGameRuleType::Boolean;
+
+    fn from_typed(value: &TypedGameRuleValue) -> Option<Self> {
+        match value {
+            TypedGameRuleValue::Boolean(value) => Some(*value),
+            TypedGameRuleValue::Integer(_) => None,
+        }
+    }
+
+    fn into_typed(self) -> TypedGameRuleValue {
+        TypedGameRuleValue::Boolean(self)
+    }
+
+    fn into_untyped(self) -> UntypedGameRuleValue {
+        UntypedGameRuleValue::Boolean(self)
+    }
+}
+
+impl GameRuleValue for i32 {
+    const TYPE: TypedGameRuleType = TypedGameRuleType::Integer;
+
+    fn from_typed(value: &TypedGameRuleValue) -> Option<Self> {
+        match value {
+            TypedGameRuleValue::Integer(value) => Some(*value),
+            TypedGameRuleValue::Boolean(_) => None,
+        }
+    }
+
+    fn into_typed(self) -> TypedGameRuleValue {
+        TypedGameRuleValue::Integer(self)
+    }
+
+    fn into_untyped(self) -> UntypedGameRuleValue {
+        UntypedGameRuleValue::Integer(self)
+    }
+}
+
+impl UntypedGameRule {
+    /// An update of the game rule `R` to `value`.
+    pub fn typed<R: GameRule>(value: R::Value) -> Self {
+        Self {
+            key: R::KEY.to_string(),
+            value: value.into_untyped(),
+        }
+    }
+}
+
+impl Client {
+    /// Returns the value of the game rule `R`, or `None` if the server doesn't have it.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use mc_rpc::gamerule::KeepInventory;
+    ///
+    /// # async fn example(client: mc_rpc::Client) -> mc_rpc::Result<()> {
+    /// if client.gamerule::<KeepInventory>().await? == Some(false) {
+    ///     client.set_gamerule::<KeepInventory>(true).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn gamerule<R: GameRule>(&self) -> Result<Option<R::Value>> {
+        // there is no request for a single rule
+        let rules = self.gamerules().get().await?;
+        Ok(rules
+            .iter()
+            .find(|rule| rule.key == R::KEY)
+            .and_then(|rule| R::Value::from_typed(&rule.value)))
+    }
+
+    /// Sets the game rule `R` to `value`, returning the value the server reports back.
+    pub async fn set_gamerule<R: GameRule>(&self, value: R::Value) -> Result<Option<R::Value>> {
+        let rule = self
+            .gamerules()
+            .update(UntypedGameRule::typed::<R>(value))
+            .await?;
+        Ok(R::Value::from_typed(&rule.value))
+    }
+}
+
+macro_rules! game_rules {
+    ($(#[doc = $doc:literal] $rule:ident, $field:ident: $value:ty = $key:literal, $default:literal;)*) => {
+        $(
+            #[doc = concat!("`", $key, "`,", $doc)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+            pub struct $rule;
+
+            impl GameRule for $rule {
+                const KEY: &'static str = $key;
+                type Value = $value;
+            }
+        )*
+
+        /// Every game rule of a server at once, converted from what `gamerules` returns.
+        ///
+        /// Rules the server doesn't have are `None`, rules these bindings don't know are kept in [`other`](Self::other).
+        /// Serialized as a map of every rule that is set, by key, e.g. `{ "keepInventory": true }`.
+        ///
+        /// ## Example
+        /// ```no_run
+        /// # async fn example(client: mc_rpc::Client) -> mc_rpc::Result<()> {
+        /// let rules: mc_rpc::gamerule::GameRules = client.gamerules().get().await?.into();
+        /// println!("Random tick speed is {:?}", rules.random_tick_speed);
+        /// # Ok(())
+        /// # }
+        /// ```
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+        pub struct GameRules {
+            $(
+                #[doc = concat!("`", $key, "`,", $doc)]
+                pub $field: Option<$value>,
+            )*
+            /// Rules not listed above, e.g. from a newer version or a mod.
+            pub other: Vec<TypedGameRule>,
+        }
+
+        impl GameRules {
+            /// The key and type of every rule listed in [`GameRules`].
+            pub const KEYS: &[(&str, TypedGameRuleType)] = &[$(($key, <$value as GameRuleValue>::TYPE)),*];
+
+            /// Every rule listed in [`GameRules`] with its vanilla default.
+            pub fn vanilla() -> Self {
+                Self {
+                    $($field: Some($default),)*
+                    other: vec![],
+                }
+            }
+
+            /// Sets the listed field of `rule`, or adds it to [`other`](Self::other).
+            pub fn insert(&mut self, rule: TypedGameRule) {
+                match rule.key.as_str() {
+                    $($key => if let Some(value) = <$value>::from_typed(&rule.value) {
+                        self.$field = Some(value);
+                        return;
+                    })*
+                    _ => (),
+                }
+                // a known key with an unexpected type is kept as is too
+                self.other.push(rule);
+            }
+
+            /// Whether no rule is set.
+            pub fn is_empty(&self) -> bool {
+                $(self.$field.is_none() &&)* self.other.is_empty()
+            }
+
+            /// Every rule that is set, as the server reports them.
+            pub fn to_rules(&self) -> Vec<TypedGameRule> {
+                let mut rules = vec![];
+                $(if let Some(value) = self.$field {
+                    rules.push(TypedGameRule {
+                        key: $key.to_string(),
+                        _type: <$value as GameRuleValue>::TYPE,
+                        value: value.into_typed(),
+                    });
+                })*
+                rules.extend(self.other.iter().cloned());
+                rules
+            }
+        }
+    };
+}
+
+impl FromIterator<TypedGameRule> for GameRules {
+    fn from_iter<I: IntoIterator<Item = TypedGameRule>>(rules: I) -> Self {
+        let mut game_rules = Self::default();
+        for rule in rules {
+            game_rules.insert(rule);
+        }
+        game_rules
+    }
+}
+
+impl From<Vec<TypedGameRule>> for GameRules {
+    fn from(rules: Vec<TypedGameRule>) -> Self {
+        rules.into_iter().collect()
+    }
+}
+
+impl Serialize for GameRules {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.to_rules()
+                .into_iter()
+                .map(|rule| (rule.key, rule.value)),
+        )
+    }
+}
+
+/// A listed rule with a value of the wrong type is an error, rules that aren't listed take either type.
+impl<'de> Deserialize<'de> for GameRules {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        let mut rules = Self::default();
+        for (key, value) in BTreeMap::<String, TypedGameRuleValue>::deserialize(deserializer)? {
+            let _type = match value {
+                TypedGameRuleValue::Boolean(_) => TypedGameRuleType::Boolean,
+                TypedGameRuleValue::Integer(_) => TypedGameRuleType::Integer,
+            };
+            if let Some((_, expected)) = Self::KEYS.iter().find(|(k, _)| *k == key)
+                && *expected != _type
+            {
+                return Err(D::Error::custom(format!(
+                    "Game rule '{key}' is {expected:?}, not {_type:?}"
+                )));
+            }
+            rules.insert(TypedGameRule { key, _type, value });
+        }
+        Ok(rules)
+    }
+}
+
+/// The same value, to update a rule with.
+impl From<TypedGameRuleValue> for UntypedGameRuleValue {
+    fn from(value: TypedGameRuleValue) -> Self {
+        match value {
+            TypedGameRuleValue::Boolean(value) => Self::Boolean(value),
+            TypedGameRuleValue::Integer(value) => Self::Integer(value),
+        }
+    }
+}
+
+game_rules! {
+    /// whether nether portals can take players to the nether.
+    AllowEnteringNetherUsingPortals, allow_entering_nether_using_portals: bool = "allowEnteringNetherUsingPortals", true;
+    /// whether fire spreads and burns out far away from players.
+    AllowFireTicksAwayFromPlayer, allow_fire_ticks_away_from_player: bool = "allowFireTicksAwayFromPlayer", false;
+    /// whether advancements are announced in chat.
+    AnnounceAdvancements, announce_advancements: bool = "announceAdvancements", true;
+    /// whether blocks destroyed by block explosions drop only some of their loot.
+    BlockExplosionDropDecay, block_explosion_drop_decay: bool = "blockExplosionDropDecay", true;
+    /// whether command blocks notify operators in chat.
+    CommandBlockOutput, command_block_output: bool = "commandBlockOutput", true;
+    /// the most blocks a single command like `/fill` can change.
+    CommandModificationBlockLimit, command_modification_block_limit: i32 = "commandModificationBlockLimit", 32768;
+    /// whether the server skips checking elytra movement.
+    DisableElytraMovementCheck, disable_elytra_movement_check: bool = "disableElytraMovementCheck", false;
+    /// whether the server skips checking player movement.
+    DisablePlayerMovementCheck, disable_player_movement_check: bool = "disablePlayerMovementCheck", false;
+    /// whether raids are disabled.
+    DisableRaids, disable_raids: bool = "disableRaids", false;
+    /// whether the time of day advances.
+    DoDaylightCycle, do_daylight_cycle: bool = "doDaylightCycle", true;
+    /// whether entities other than mobs drop items.
+    DoEntityDrops, do_entity_drops: bool = "doEntityDrops", true;
+    /// whether fire spreads and burns out.
+    DoFireTick, do_fire_tick: bool = "doFireTick", true;
+    /// whether players respawn without the death screen.
+    DoImmediateRespawn, do_immediate_respawn: bool = "doImmediateRespawn", false;
+    /// whether phantoms spawn at night.
+    DoInsomnia, do_insomnia: bool = "doInsomnia", true;
+    /// whether players can only craft unlocked recipes.
+    DoLimitedCrafting, do_limited_crafting: bool = "doLimitedCrafting", false;
+    /// whether mobs drop loot.
+    DoMobLoot, do_mob_loot: bool = "doMobLoot", true;
+    /// whether mobs spawn naturally.
+    DoMobSpawning, do_mob_spawning: bool = "doMobSpawning", true;
+    /// whether pillager patrols spawn.
+    DoPatrolSpawning, do_patrol_spawning: bool = "doPatrolSpawning", true;
+    /// whether broken blocks drop items.
+    DoTileDrops, do_tile_drops: bool = "doTileDrops", true;
+    /// whether wandering traders spawn.
+    DoTraderSpawning, do_trader_spawning: bool = "doTraderSpawning", true;
+    /// whether vines spread.
+    DoVinesSpread, do_vines_spread: bool = "doVinesSpread", true;
+    /// whether wardens spawn.
+    DoWardenSpawning, do_warden_spawning: bool = "doWardenSpawning", true;
+    /// whether the weather changes.
+    DoWeatherCycle, do_weather_cycle: bool = "doWeatherCycle", true;
+    /// whether players take drowning damage.
+    DrowningDamage, drowning_damage: bool = "drowningDamage", true;
+    /// whether thrown ender pearls vanish when their thrower dies.
+    EnderPearlsVanishOnDeath, ender_pearls_vanish_on_death: bool = "enderPearlsVanishOnDeath", true;
+    /// whether players take fall damage.
+    FallDamage, fall_damage: bool = "fallDamage", true;
+    /// whether players take fire damage.
+    FireDamage, fire_damage: bool = "fireDamage", true;
+    /// whether angered neutral mobs calm down when their target dies.
+    ForgiveDeadPlayers, forgive_dead_players: bool = "forgiveDeadPlayers", true;
+    /// whether players take freeze damage.
+    FreezeDamage, freeze_damage: bool = "freezeDamage", true;
+    /// whether some sounds are heard by every player.
+    GlobalSoundEvents, global_sound_events: bool = "globalSoundEvents", true;
+    /// whether players keep their items on death.
+    KeepInventory, keep_inventory: bool = "keepInventory", false;
+    /// whether lava can form new source blocks.
+    LavaSourceConversion, lava_source_conversion: bool = "lavaSourceConversion", false;
+    /// whether players see the locator bar.
+    LocatorBar, locator_bar: bool = "locatorBar", true;
+    /// whether operator commands are logged.
+    LogAdminCommands, log_admin_commands: bool = "logAdminCommands", true;
+    /// the most commands a single function can run.
+    MaxCommandChainLength, max_command_chain_length: i32 = "maxCommandChainLength", 65536;
+    /// the most contexts a single command can fork into.
+    MaxCommandForkCount, max_command_fork_count: i32 = "maxCommandForkCount", 65536;
+    /// how many entities can push each other before they take damage.
+    MaxEntityCramming, max_entity_cramming: i32 = "maxEntityCramming", 24;
+    /// whether blocks destroyed by mob explosions drop only some of their loot.
+    MobExplosionDropDecay, mob_explosion_drop_decay: bool = "mobExplosionDropDecay", true;
+    /// whether mobs can change blocks and pick up items.
+    MobGriefing, mob_griefing: bool = "mobGriefing", true;
+    /// whether players regenerate health from food.
+    NaturalRegeneration, natural_regeneration: bool = "naturalRegeneration", true;
+    /// how many ticks creative players stand in a nether portal before it takes them.
+    PlayersNetherPortalCreativeDelay, players_nether_portal_creative_delay: i32 = "playersNetherPortalCreativeDelay", 0;
+    /// how many ticks other players stand in a nether portal before it takes them.
+    PlayersNetherPortalDefaultDelay, players_nether_portal_default_delay: i32 = "playersNetherPortalDefaultDelay", 80;
+    /// the percentage of players that have to sleep to skip the night.
+    PlayersSleepingPercentage, players_sleeping_percentage: i32 = "playersSleepingPercentage", 100;
+    /// whether projectiles can break blocks like decorated pots.
+    ProjectilesCanBreakBlocks, projectiles_can_break_blocks: bool = "projectilesCanBreakBlocks", true;
+    /// whether players can damage each other.
+    Pvp, pvp: bool = "pvp", true;
+    /// how many random block ticks happen per chunk section and tick.
+    RandomTickSpeed, random_tick_speed: i32 = "randomTickSpeed", 3;
+    /// whether the debug screen hides details.
+    ReducedDebugInfo, reduced_debug_info: bool = "reducedDebugInfo", false;
+    /// whether commands send feedback to the players running them.
+    SendCommandFeedback, send_command_feedback: bool = "sendCommandFeedback", true;
+    /// whether player deaths are announced in chat.
+    ShowDeathMessages, show_death_messages: bool = "showDeathMessages", true;
+    /// how many layers of snow can build up while it snows.
+    SnowAccumulationHeight, snow_accumulation_height: i32 = "snowAccumulationHeight", 1;
+    /// how many chunks around the world spawn stay loaded.
+    SpawnChunkRadius, spawn_chunk_radius: i32 = "spawnChunkRadius", 2;
+    /// how many blocks around the world spawn players spawn in.
+    SpawnRadius, spawn_radius: i32 = "spawnRadius", 10;
+    /// whether spawner blocks spawn mobs.
+    SpawnerBlocksEnabled, spawner_blocks_enabled: bool = "spawnerBlocksEnabled", true;
+    /// whether spectators generate chunks.
+    SpectatorsGenerateChunks, spectators_generate_chunks: bool = "spectatorsGenerateChunks", true;
+    /// whether blocks destroyed by TNT drop only some of their loot.
+    TntExplosionDropDecay, tnt_explosion_drop_decay: bool = "tntExplosionDropDecay", false;
+    /// whether TNT explodes.
+    TntExplodes, tnt_explodes: bool = "tntExplodes", true;
+    /// whether angered neutral mobs attack every nearby player.
+    UniversalAnger, universal_anger: bool = "universalAnger", false;
+    /// whether water can form new source blocks.
+    WaterSourceConversion, water_source_conversion: bool = "waterSourceConversion", true;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn serialized_as_a_map_of_set_rules() {
+        let rules = GameRules {
+            keep_inventory: Some(true),
+            random_tick_speed: Some(10),
+            ..Default::default()
+        };
+        let value = json!({ "keepInventory": true, "randomTickSpeed": 10 });
+
+        assert_eq!(serde_json::to_value(&rules).unwrap(), value);
+        assert_eq!(serde_json::from_value::<GameRules>(value).unwrap(), rules);
+        assert_eq!(
+            serde_json::to_value(GameRules::default()).unwrap(),
+            json!({})
+        );
+    }
+
+    #[test]
+    fn unknown_rules_are_kept() {
+        let rules = serde_json::from_value::<GameRules>(json!({ "modRule": 3 })).unwrap();
+        assert!(!rules.is_empty());
+        assert_eq!(
+            rules.other,
+            [TypedGameRule {
+                key: "modRule".to_string(),
+                _type: TypedGameRuleType::Integer,
+                value: TypedGameRuleValue::Integer(3),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&rules).unwrap(),
+            json!({ "modRule": 3 })
+        );
+    }
+
+    #[test]
+    fn listed_rules_have_to_be_of_their_type() {
+        let error = serde_json::from_value::<GameRules>(json!({ "keepInventory": 1 })).unwrap_err();
+        assert!(error.to_string().contains("keepInventory"), "{error}");
+        assert!(serde_json::from_value::<GameRules>(json!({ "randomTickSpeed": true })).is_err());
+        assert!(serde_json::from_value::<GameRules>(json!({ "keepInventory": "yes" })).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use std::convert::Infallible;
+
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{
+        IntoResponse, Response, Sse,
+        sse::{Event, KeepAlive},
+    },
+};
+use pale::{PaleError, Result};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::{
+    handler::{HandlerError, Notification},
+    notification::NotificationError,
+};
+
+/// A route of the REST gateway, see `Client::gateway`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GatewayRoute {
+    pub http_method: &'static str,
+    pub path: &'static str,
+    /// The request the route forwards to.
+    pub method: &'static str,
+    /// The names of the request's params, the body is the only param itself or an array of all of them.
+    pub params: &'static [&'static str],
+}
+
+/// Answers with the JSON result of a request, or `{"message": .., "code": ..}` for the error it failed with.
+///
+/// `code` is the JSON-RPC error code if the server answered with an error, `null` otherwise.
+pub(crate) fn gateway_response<T: Serialize>(result: Result<T>) -> Response {
+    let error = match result {
+        Ok(result) => return Json(result).into_response(),
+        Err(error) => error,
+    };
+
+    let status = match &error {
+        PaleError::RPC(e) if e.code == HandlerError::INVALID_PARAMS => StatusCode::BAD_REQUEST,
+        PaleError::RPC(e) if e.code == HandlerError::METHOD_NOT_FOUND => {
+            StatusCode::NOT_IMPLEMENTED
+        }
+        PaleError::RequestTimeout
+        | PaleError::RequestTimeoutOrError
+        | PaleError::ClientTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        PaleError::ClientDisconnected => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    let (message, code) = match error {
+        PaleError::RPC(e) => (e.message, Some(e.code)),
+        error => (error.to_string(), None),
+    };
+    (status, Json(json!({ "message": message, "code": code }))).into_response()
+}
+
+/// Streams every notification as a Server-Sent Event named after its method, with its positional params as data.
+///
+/// A notification that couldn't be decoded, or was dropped because the stream lagged behind, is sent as an `error` event.
+pub(crate) fn gateway_events<E, S>(events: Result<S>) -> Response
+where
+    E: Notification,
+    S: Stream<Item = std::result::Result<E, NotificationError>> + Send + 'static,
+{
+    let events = match events {
+        Ok(events) => events,
+        Err(error) => return gateway_response::<()>(Err(error)),
+    };
+
+    let events = events.map(|event| {
+        Ok::<Event, Infallible>(match event {
+            Ok(event) => Event::default()
+                .event(event.method().trim_start_matches("minecraft:notification/"))
+                .data(event.params().to_string()),
+            Err(error) => Event::default()
+                .event("error")
+                .data(json!({ "message": error.to_string() }).to_string()),
+        })
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// The OpenAPI document of `routes`, with the descriptions and JSON schemas of the OpenRPC `schema` they forward to.
+///
+/// The schema's `#/components/schemas` are copied as they are, so its references stay valid.
+pub(crate) fn openapi(schema: &str, routes: &[GatewayRoute]) -> Value {
+    let schema: Value = serde_json::from_str(schema).expect("the schema was read at build time");
+    let methods = schema["methods"].as_array().map(Vec::as_slice);
+
+    let mut paths = Map::new();
+    for route in routes {
+        let Some(method) = methods
+            .unwrap_or_default()
+            .iter()
+            .find(|m| m["name"] == route.method)
+        else {
+            continue;
+        };
+
+        let mut operation = json!({
+            "operationId": route.method,
+            "summary": method["description"],
+            "responses": {
+                "200": {
+                    "description": method["result"]["name"].as_str().unwrap_or("The result"),
+                    "content": { "application/json": { "schema": method["result"]["schema"] } },
+                },
+                "default": { "$ref": "#/components/responses/Error" },
+            },
+        });
+
+        let params = method["params"].as_array().map(Vec::as_slice);
+        let body = match params.unwrap_or_default() {
+            [] => None,
+            [param] => Some(param["schema"].clone()),
+            params => Some(json!({
+                "type": "array",
+                "prefixItems": params.iter().map(|p| &p["schema"]).collect::<Vec<&Value>>(),
+                "minItems": params.len(),
+                "maxItems": params.len(),
+            })),
+        };
+        if let Some(body) = body {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": body } },
+            });
+        }
+
+        let path = paths.entry(route.path).or_insert_with(|| json!({}));
+        path[route.http_method.to_lowercase()] = operation;
+    }
+
+    paths.insert(
+        "/events".to_string(),
+        json!({
+            "get": {
+                "operationId": "events",
+                "summary": "Stream every notification, named after its method with its params as data",
+                "responses": {
+                    "200": {
+                        "description": "Server-Sent Events",
+                        "content": { "text/event-stream": { "schema": { "type": "string" } } },
+                    },
+                },
+            },
+        }),
+    );
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": format!("{} REST gateway", schema["info"]["title"].as_str().unwrap_or_default()),
+            "version": schema["info"]["version"],
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schema["components"]["schemas"],
+            "responses": {
+                "Error": {
+                    "description": "The request failed, `code` is the JSON-RPC error code if the server answered with an error",
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "type": "object",
+                                "properties": {
+                                    "message": { "type": "string" },
+                                    "code": { "type": ["integer", "null"] },
+                                },
+                                "required": ["message", "code"],
+                            },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pale::RPCError;
+
+    use super::*;
+    use crate::{GATEWAY_ROUTES, gateway_openapi};
+
+    fn rpc_error(code: i32) -> PaleError {
+        PaleError::RPC(
+            serde_json::from_value::<RPCError>(json!({ "code": code, "message": "Nope" })).unwrap(),
+        )
+    }
+
+    async fn answer(result: Result<Value>) -> (StatusCode, Value) {
+        let response = gateway_response(result);
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn results_are_the_body() {
+        assert_eq!(
+            answer(Ok(json!(["Steve"]))).await,
+            (StatusCode::OK, json!(["Steve"]))
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_map_to_a_status() {
+        for (error, status, code) in [
+            (
+                rpc_error(HandlerError::INVALID_PARAMS),
+                StatusCode::BAD_REQUEST,
+                json!(HandlerError::INVALID_PARAMS),
+            ),
+            (
+                rpc_error(HandlerError::METHOD_NOT_FOUND),
+                StatusCode::NOT_IMPLEMENTED,
+                json!(HandlerError::METHOD_NOT_FOUND),
+            ),
+            (
+                rpc_error(HandlerError::INTERNAL_ERROR),
+                StatusCode::BAD_GATEWAY,
+                json!(HandlerError::INTERNAL_ERROR),
+            ),
+            (
+                PaleError::RequestTimeout,
+                StatusCode::GATEWAY_TIMEOUT,
+                Value::Null,
+            ),
+            (
+                PaleError::ClientDisconnected,
+                StatusCode::SERVICE_UNAVAILABLE,
+                Value::Null,
+            ),
+            (
+                PaleError::NoReturnedRPCData,
+                StatusCode::BAD_GATEWAY,
+                Value::Null,
+            ),
+        ] {
+            let message = match &error {
+                PaleError::RPC(e) => e.message.clone(),
+                error => error.to_string(),
+            };
+            assert_eq!(
+                answer(Err(error)).await,
+                (status, json!({ "message": message, "code": code }))
+            );
+        }
+    }
+
+    #[test]
+    fn openapi_describes_every_route() {
+        let openapi = gateway_openapi();
+        for route in GATEWAY_ROUTES {
+            let operation = &openapi["paths"][route.path][route.http_method.to_lowercase()];
+            assert_eq!(
+                operation["operationId"], route.method,
+                "{} {}",
+                route.http_method, route.path
+            );
+            assert!(operation["responses"]["200"].is_object());
+            assert_eq!(
+                operation.get("requestBody").is_some(),
+                !route.params.is_empty(),
+                "{} {}",
+                route.http_method,
+                route.path
+            );
+        }
+        assert!(openapi["paths"]["/events"]["get"].is_object());
+        assert!(openapi["components"]["schemas"].is_object());
+    }
+}
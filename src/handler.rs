@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use thiserror::Error;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+/// A JSON-RPC error returned by a `ManagementHandler`, sent to the client as the `error` of its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Error)]
+#[error("{message} ({code})")]
+pub struct HandlerError {
+    pub code: i32,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub data: Option<Value>,
+}
+
+impl HandlerError {
+    /// The message isn't valid JSON.
+    pub const PARSE_ERROR: i32 = -32700;
+    /// The message isn't a valid JSON-RPC request.
+    pub const INVALID_REQUEST: i32 = -32600;
+    /// The method doesn't exist or isn't implemented.
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    /// The params don't match what the schema describes.
+    pub const INVALID_PARAMS: i32 = -32602;
+    /// Anything else that went wrong while handling the request.
+    pub const INTERNAL_ERROR: i32 = -32603;
+
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(
+            Self::METHOD_NOT_FOUND,
+            format!("Method not found: {method}"),
+        )
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(Self::INTERNAL_ERROR, message)
+    }
+}
+
+/// Anything that answers JSON-RPC messages, implemented by the `Dispatcher` of every protocol version.
+pub trait MessageHandler: Clone + Send + Sync + 'static {
+    /// Handles a single JSON-RPC message, a request or a batch, returning the response to send back, if any.
+    fn handle(&self, message: &str) -> impl Future<Output = Option<String>> + Send;
+}
+
+/// A notification that can be sent with a [`Notifier`], implemented by the `ServerEvent` of every protocol version.
+pub trait Notification {
+    /// The `minecraft:notification/*` method.
+    fn method(&self) -> &'static str;
+    /// The positional `params`.
+    fn params(&self) -> Value;
+}
+
+/// Handles a JSON-RPC message, either a single request or a batch of them, passing each request to `handle_request`.
+///
+/// Batched requests are handled one at a time and answered with a single array, leaving out those without a response.
+pub(crate) async fn handle_message<F, Fut>(message: &str, handle_request: F) -> Option<String>
+where
+    F: Fn(Value) -> Fut,
+    Fut: Future<Output = Option<Value>>,
+{
+    let message = match serde_json::from_str::<Value>(message) {
+        Ok(message) => message,
+        Err(e) => {
+            let error = HandlerError::new(HandlerError::PARSE_ERROR, e.to_string());
+            return Some(response(Value::Null, Err(error)).to_string());
+        }
+    };
+
+    match message {
+        Value::Array(batch) if batch.is_empty() => {
+            let error = HandlerError::new(HandlerError::INVALID_REQUEST, "Empty batch");
+            Some(response(Value::Null, Err(error)).to_string())
+        }
+        Value::Array(batch) => {
+            let mut responses = vec![];
+            for request in batch {
+                responses.extend(handle_request(request).await);
+            }
+            (!responses.is_empty()).then(|| Value::Array(responses).to_string())
+        }
+        request => handle_request(request).await.map(|r| r.to_string()),
+    }
+}
+
+/// Splits a JSON-RPC request into its `id`, `method` and `params`,
+/// or returns the error response if it isn't one.
+pub(crate) fn parse_request(request: Value) -> Result<(Option<Value>, String, Value), Value> {
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(|m| m.as_str()) else {
+        return Err(response(
+            id.unwrap_or_default(),
+            Err(HandlerError::new(
+                HandlerError::INVALID_REQUEST,
+                "Missing method",
+            )),
+        ));
+    };
+    let params = request.get("params").cloned().unwrap_or_default();
+
+    Ok((id, method.to_string(), params))
+}
+
+pub(crate) fn response(id: Value, result: Result<Value, HandlerError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+/// Reads a request param either by `name`, `{"name": param}`, or by `index`, `[param]`.
+pub(crate) fn request_param<T: DeserializeOwned>(
+    params: &Value,
+    index: usize,
+    name: &str,
+) -> Result<T, HandlerError> {
+    let param = match params {
+        Value::Object(params) => params.get(name),
+        Value::Array(params) => params.get(index),
+        _ => None,
+    };
+    let Some(param) = param else {
+        return Err(HandlerError::invalid_params(format!(
+            "Missing param '{name}'"
+        )));
+    };
+
+    serde_json::from_value(param.clone())
+        .map_err(|e| HandlerError::invalid_params(format!("Invalid param '{name}': {e}")))
+}
+
+pub(crate) fn to_result<T: Serialize>(
+    result: Result<T, HandlerError>,
+) -> Result<Value, HandlerError> {
+    serde_json::to_value(result?).map_err(|e| HandlerError::internal(e.to_string()))
+}
+
+/// Pushes `minecraft:notification/*` messages to every connection of a server.
+///
+/// Transports [`subscribe`](Notifier::subscribe) once per connection and send every message they receive as is.
+#[derive(Debug, Clone)]
+pub struct Notifier(Sender<String>);
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl Notifier {
+    /// Creates a notifier that buffers up to `capacity` messages for connections that fall behind.
+    pub fn new(capacity: usize) -> Self {
+        Self(broadcast::channel(capacity).0)
+    }
+
+    /// Sends `event` to every subscribed connection, returning how many there were.
+    pub fn notify(&self, event: &impl Notification) -> usize {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": event.method(),
+            "params": event.params(),
+        });
+        // nobody being connected isn't an error
+        self.0.send(notification.to_string()).unwrap_or(0)
+    }
+
+    /// Returns a [`Receiver`] of every serialized notification sent after this call.
+    pub fn subscribe(&self) -> Receiver<String> {
+        self.0.subscribe()
+    }
+}
+
+/// Serves `dispatcher`, usually a `Dispatcher`, over WebSocket to every connection accepted from `listener`, until the returned future is dropped.
+///
+/// Every connection also receives the notifications sent with `notifier`.
+#[cfg(feature = "server")]
+pub async fn serve<D: MessageHandler>(
+    listener: tokio::net::TcpListener,
+    dispatcher: D,
+    notifier: Notifier,
+) -> std::io::Result<()> {
+    // dropping the set when this future is dropped also aborts every connection
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        let (stream, _) = listener.accept().await?;
+        connections.spawn(connection(stream, dispatcher.clone(), notifier.subscribe()));
+    }
+}
+
+/// Serves a single client until either side closes the connection.
+///
+/// Requests are handled one at a time, in the order they arrive.
+#[cfg(feature = "server")]
+async fn connection<D: MessageHandler>(
+    stream: tokio::net::TcpStream,
+    dispatcher: D,
+    mut notifications: Receiver<String>,
+) {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut write, mut read) = ws.split();
+
+    loop {
+        let frame = tokio::select! {
+            msg = read.next() => match msg {
+                Some(Ok(Message::Text(text))) => match dispatcher.handle(text.as_str()).await {
+                    Some(response) => response,
+                    None => continue,
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            notification = notifications.recv() => match notification {
+                Ok(notification) => notification,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+        };
+
+        if write.send(Message::text(frame)).await.is_err() {
+            return;
+        }
+    }
+}
@@ -1 +1,25 @@
+mod batch;
+mod check;
+mod compat;
+mod dynamic;
+mod expiry;
+mod fleet;
+pub mod gamerule;
+#[cfg(feature = "gateway")]
+mod gateway;
+mod handler;
+mod list_files;
+mod message;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod notification;
+mod player;
+mod properties;
+mod reconcile;
+mod request;
+mod settings;
+mod spec;
+
 include!(concat!(env!("OUT_DIR"), "/json_rpc_bindings.rs"));
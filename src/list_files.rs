@@ -0,0 +1,424 @@
+//! The list files a server keeps next to its world, e.g. `whitelist.json`, read into and written from the API's types.
+//!
+//! There are no requests of their own to migrate them, the lists read from a file go to the `set` request of their list.
+//!
+//! | File                  | Read                                                            | Pushed with                                             |
+//! |-----------------------|-----------------------------------------------------------------|---------------------------------------------------------|
+//! | `whitelist.json`      | [`Player::from_whitelist_json`](crate::Player)                  | `client.allowlist().set(..)`, `minecraft:allowlist/set` |
+//! | `ops.json`            | [`Operator::from_ops_json`](crate::Operator)                    | `client.operators().set(..)`, `minecraft:operators/set` |
+//! | `banned-players.json` | [`UserBan::from_banned_players_json`](crate::UserBan)           | `client.bans().set(..)`, `minecraft:bans/set`           |
+//! | `banned-ips.json`     | [`IpBan::from_banned_ips_json`](crate::IpBan)                   | `client.ip_bans().set(..)`, `minecraft:ip_bans/set`     |
+//!
+//! ## Example
+//! ```no_run
+//! # use mc_rpc::{IpBan, Operator, Player, UserBan};
+//! # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {
+//! let read = |file| std::fs::read_to_string(file);
+//! client.allowlist().set(Player::from_whitelist_json(&read("whitelist.json")?)?).await?;
+//! client.operators().set(Operator::from_ops_json(&read("ops.json")?)?).await?;
+//! client.bans().set(UserBan::from_banned_players_json(&read("banned-players.json")?)?).await?;
+//! client.ip_bans().set(IpBan::from_banned_ips_json(&read("banned-ips.json")?)?).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{
+    IpBan, Operator, Player, PlayerId, UserBan,
+    expiry::{Expiry, ban_list_time},
+};
+
+#[derive(Serialize, Deserialize)]
+struct FilePlayer {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    uuid: Option<PlayerId>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileOperator {
+    #[serde(flatten)]
+    player: FilePlayer,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    level: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    bypasses_player_limit: Option<bool>,
+}
+
+/// The fields every ban has, vanilla writes all of them.
+#[derive(Serialize, Deserialize)]
+struct FileBan {
+    /// Not known to the API, ignored when read and set to now when written.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    source: Option<String>,
+    /// Written in the server's own format, it reads an expiry it can't parse as permanent.
+    #[serde(serialize_with = "serialize_expires", default)]
+    expires: Option<Expiry>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileUserBan {
+    #[serde(flatten)]
+    player: FilePlayer,
+    #[serde(flatten)]
+    ban: FileBan,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileIpBan {
+    ip: String,
+    #[serde(flatten)]
+    ban: FileBan,
+}
+
+impl From<FilePlayer> for Player {
+    fn from(player: FilePlayer) -> Self {
+        Self {
+            id: player.uuid,
+            name: player.name,
+        }
+    }
+}
+
+impl From<Player> for FilePlayer {
+    fn from(player: Player) -> Self {
+        Self {
+            uuid: player.id,
+            name: player.name,
+        }
+    }
+}
+
+impl FileBan {
+    fn new(source: &Option<String>, expires: Option<Expiry>, reason: &Option<String>) -> Self {
+        Self {
+            created: Some(ban_list_time(SystemTime::now())),
+            source: source.clone(),
+            expires,
+            reason: reason.clone(),
+        }
+    }
+}
+
+/// Writes `entries` like the server does, as a JSON array indented by two spaces.
+fn to_file<T: Serialize>(entries: impl Iterator<Item = T>) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&entries.collect::<Vec<T>>())
+}
+
+fn serialize_expires<S: Serializer>(
+    expires: &Option<Expiry>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&expires.unwrap_or(Expiry::Permanent).to_ban_list())
+}
+
+impl Player {
+    /// Reads the players of a `whitelist.json` file.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use mc_rpc::Player;
+    /// # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let players = Player::from_whitelist_json(&std::fs::read_to_string("whitelist.json")?)?;
+    /// client.allowlist().set(players).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_whitelist_json(json: &str) -> serde_json::Result<Vec<Player>> {
+        let players: Vec<FilePlayer> = serde_json::from_str(json)?;
+        Ok(players.into_iter().map(Player::from).collect())
+    }
+
+    /// Writes `players` as a `whitelist.json` file.
+    pub fn to_whitelist_json(players: &[Player]) -> serde_json::Result<String> {
+        to_file(players.iter().cloned().map(FilePlayer::from))
+    }
+}
+
+impl Operator {
+    /// Reads the operators of an `ops.json` file.
+    pub fn from_ops_json(json: &str) -> serde_json::Result<Vec<Operator>> {
+        let operators: Vec<FileOperator> = serde_json::from_str(json)?;
+        Ok(operators
+            .into_iter()
+            .map(|operator| Operator {
+                bypasses_player_limit: operator.bypasses_player_limit,
+                permission_level: operator.level,
+                player: operator.player.into(),
+            })
+            .collect())
+    }
+
+    /// Writes `operators` as an `ops.json` file.
+    pub fn to_ops_json(operators: &[Operator]) -> serde_json::Result<String> {
+        to_file(operators.iter().map(|operator| FileOperator {
+            player: operator.player.clone().into(),
+            level: operator.permission_level,
+            bypasses_player_limit: operator.bypasses_player_limit,
+        }))
+    }
+}
+
+impl UserBan {
+    /// Reads the bans of a `banned-players.json` file.
+    pub fn from_banned_players_json(json: &str) -> serde_json::Result<Vec<UserBan>> {
+        let bans: Vec<FileUserBan> = serde_json::from_str(json)?;
+        Ok(bans
+            .into_iter()
+            .map(|ban| UserBan {
+                expires: ban.ban.expires,
+                player: ban.player.into(),
+                reason: ban.ban.reason,
+                source: ban.ban.source,
+            })
+            .collect())
+    }
+
+    /// Writes `bans` as a `banned-players.json` file, created now.
+    pub fn to_banned_players_json(bans: &[UserBan]) -> serde_json::Result<String> {
+        to_file(bans.iter().map(|ban| FileUserBan {
+            player: ban.player.clone().into(),
+            ban: FileBan::new(&ban.source, ban.expires, &ban.reason),
+        }))
+    }
+}
+
+impl IpBan {
+    /// Reads the bans of a `banned-ips.json` file.
+    pub fn from_banned_ips_json(json: &str) -> serde_json::Result<Vec<IpBan>> {
+        let bans: Vec<FileIpBan> = serde_json::from_str(json)?;
+        Ok(bans
+            .into_iter()
+            .map(|ban| IpBan {
+                expires: ban.ban.expires,
+                ip: ban.ip,
+                reason: ban.ban.reason,
+                source: ban.ban.source,
+            })
+            .collect())
+    }
+
+    /// Writes `bans` as a `banned-ips.json` file, created now.
+    pub fn to_banned_ips_json(bans: &[IpBan]) -> serde_json::Result<String> {
+        to_file(bans.iter().map(|ban| FileIpBan {
+            ip: ban.ip.clone(),
+            ban: FileBan::new(&ban.source, ban.expires, &ban.reason),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    const NOTCH: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+    const JEB: &str = "853c80ef-3c37-49fd-aa49-938b674adae6";
+
+    fn player(id: &str, name: &str) -> Player {
+        Player {
+            id: Some(id.parse().unwrap()),
+            name: Some(name.to_string()),
+        }
+    }
+
+    fn at(seconds: u64) -> Option<Expiry> {
+        Some(Expiry::At(UNIX_EPOCH + Duration::from_secs(seconds)))
+    }
+
+    #[test]
+    fn vanilla_whitelist() {
+        let json = format!(
+            r#"[
+  {{
+    "uuid": "{NOTCH}",
+    "name": "Notch"
+  }},
+  {{
+    "uuid": "{JEB}",
+    "name": "jeb_"
+  }}
+]"#
+        );
+
+        let players = Player::from_whitelist_json(&json).unwrap();
+        assert_eq!(players, [player(NOTCH, "Notch"), player(JEB, "jeb_")]);
+        assert_eq!(Player::to_whitelist_json(&players).unwrap(), json);
+    }
+
+    #[test]
+    fn vanilla_ops() {
+        let json = format!(
+            r#"[
+  {{
+    "uuid": "{NOTCH}",
+    "name": "Notch",
+    "level": 4,
+    "bypassesPlayerLimit": false
+  }}
+]"#
+        );
+
+        let operators = Operator::from_ops_json(&json).unwrap();
+        assert_eq!(
+            operators,
+            [Operator {
+                bypasses_player_limit: Some(false),
+                permission_level: Some(4),
+                player: player(NOTCH, "Notch"),
+            }]
+        );
+        assert_eq!(Operator::to_ops_json(&operators).unwrap(), json);
+    }
+
+    #[test]
+    fn vanilla_banned_players() {
+        let json = format!(
+            r#"[
+  {{
+    "uuid": "{NOTCH}",
+    "name": "Notch",
+    "created": "2025-10-18 14:00:00 +0200",
+    "source": "Server",
+    "expires": "forever",
+    "reason": "Banned by an operator."
+  }},
+  {{
+    "uuid": "{JEB}",
+    "name": "jeb_",
+    "created": "2025-10-18 14:00:00 +0200",
+    "source": "Notch",
+    "expires": "2025-10-25 14:00:00 +0200",
+    "reason": "Cool down for a week"
+  }}
+]"#
+        );
+
+        let bans = UserBan::from_banned_players_json(&json).unwrap();
+        assert_eq!(
+            bans,
+            [
+                UserBan {
+                    source: Some("Server".into()),
+                    ..UserBan::permanent(player(NOTCH, "Notch"), "Banned by an operator.")
+                },
+                UserBan {
+                    expires: at(1_761_393_600),
+                    player: player(JEB, "jeb_"),
+                    reason: Some("Cool down for a week".into()),
+                    source: Some("Notch".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn vanilla_banned_ips() {
+        let json = r#"[
+  {
+    "ip": "192.168.0.1",
+    "created": "2025-10-18 14:00:00 +0200",
+    "source": "Server",
+    "expires": "forever",
+    "reason": "Banned by an operator."
+  },
+  {
+    "ip": "192.168.0.2",
+    "created": "2025-10-18 14:00:00 +0200",
+    "source": "Server",
+    "expires": "2025-10-25 14:00:00 +0200",
+    "reason": "Spam"
+  }
+]"#;
+
+        let bans = IpBan::from_banned_ips_json(json).unwrap();
+        assert_eq!(
+            bans,
+            [
+                IpBan {
+                    source: Some("Server".into()),
+                    ..IpBan::permanent("192.168.0.1", "Banned by an operator.")
+                },
+                IpBan {
+                    expires: at(1_761_393_600),
+                    ip: "192.168.0.2".into(),
+                    reason: Some("Spam".into()),
+                    source: Some("Server".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bans_round_trip() {
+        let bans = [
+            UserBan::permanent(player(NOTCH, "Notch"), "Griefing"),
+            UserBan {
+                expires: at(1_761_393_600),
+                player: Player::by_name("Steve"),
+                reason: None,
+                source: Some("Console".into()),
+            },
+        ];
+        let json = UserBan::to_banned_players_json(&bans).unwrap();
+        assert_eq!(UserBan::from_banned_players_json(&json).unwrap(), bans);
+
+        // written in the server's own format, created now
+        let written: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(written[0]["expires"], "forever");
+        assert_eq!(written[1]["expires"], "2025-10-25 12:00:00 +0000");
+        assert_eq!(
+            written[0]["created"]
+                .as_str()
+                .unwrap()
+                .parse::<Expiry>()
+                .ok()
+                .map(|e| e.is_expired()),
+            Some(true)
+        );
+
+        let ip_bans = [
+            IpBan::permanent("192.168.0.1", "Spam"),
+            IpBan {
+                expires: at(1_761_393_600),
+                ip: "192.168.0.2".into(),
+                reason: Some("Spam".into()),
+                source: None,
+            },
+        ];
+        let json = IpBan::to_banned_ips_json(&ip_bans).unwrap();
+        assert_eq!(IpBan::from_banned_ips_json(&json).unwrap(), ip_bans);
+    }
+
+    #[test]
+    fn players_and_operators_round_trip() {
+        let players = [player(NOTCH, "Notch"), Player::by_name("Steve")];
+        let json = Player::to_whitelist_json(&players).unwrap();
+        assert_eq!(Player::from_whitelist_json(&json).unwrap(), players);
+
+        let operators = [
+            Operator {
+                bypasses_player_limit: Some(true),
+                permission_level: Some(2),
+                player: player(JEB, "jeb_"),
+            },
+            Operator {
+                bypasses_player_limit: None,
+                permission_level: None,
+                player: Player::by_name("Steve"),
+            },
+        ];
+        let json = Operator::to_ops_json(&operators).unwrap();
+        assert_eq!(Operator::from_ops_json(&json).unwrap(), operators);
+    }
+}
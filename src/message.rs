@@ -0,0 +1,92 @@
+use thiserror::Error;
+
+use crate::{Message, SystemMessage};
+
+/// Why a [`Message`] can't be sent, see [`Message::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Error)]
+pub enum MessageError {
+    #[error("A message needs either a literal or a translatable text")]
+    Empty,
+    #[error("A message can't be both literal and translatable")]
+    Ambiguous,
+    #[error("Only translatable messages take params")]
+    LiteralParams,
+}
+
+/// A chat message, either literal text or a translation key the client resolves in its own language.
+///
+/// ## Example
+/// ```
+/// use mc_rpc::Message;
+///
+/// let hello: Message = "Hello".into();
+/// let joined = Message::translatable("multiplayer.player.joined").arg("Steve");
+/// assert!(hello.validate().is_ok() && joined.validate().is_ok());
+/// ```
+impl Message {
+    /// A message shown exactly as `text`.
+    pub fn literal(text: impl Into<String>) -> Self {
+        Self {
+            literal: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// A message translated from `key` by the client, e.g. `multiplayer.player.joined`.
+    pub fn translatable(key: impl Into<String>) -> Self {
+        Self {
+            translatable: Some(key.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Appends a param to fill into the translation, in the order of its `%s` placeholders.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.translatable_params
+            .get_or_insert_default()
+            .push(arg.into());
+        self
+    }
+
+    /// Appends every param of `args`, see [`arg`](Self::arg).
+    pub fn args(self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        args.into_iter().fold(self, |message, arg| message.arg(arg))
+    }
+
+    /// Checks that exactly one of `literal` and `translatable` is set, and params only come with a translation.
+    ///
+    /// Messages are checked before they are sent in a request, so an invalid one fails the request instead of reaching the server.
+    pub fn validate(&self) -> Result<(), MessageError> {
+        match (&self.literal, &self.translatable) {
+            (None, None) => Err(MessageError::Empty),
+            (Some(_), Some(_)) => Err(MessageError::Ambiguous),
+            (Some(_), None) if self.translatable_params.is_some() => {
+                Err(MessageError::LiteralParams)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<&str> for Message {
+    fn from(text: &str) -> Self {
+        Self::literal(text)
+    }
+}
+
+impl From<String> for Message {
+    fn from(text: String) -> Self {
+        Self::literal(text)
+    }
+}
+
+/// A system message to every player, in chat.
+impl From<Message> for SystemMessage {
+    fn from(message: Message) -> Self {
+        Self {
+            message,
+            overlay: None,
+            receiving_players: None,
+        }
+    }
+}
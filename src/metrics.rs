@@ -0,0 +1,281 @@
+use std::time::{Duration, Instant};
+
+use axum::{http::header, routing::get};
+use pale::Result;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder, core::Collector,
+};
+use serde_json::Value;
+use tokio_stream::StreamExt;
+
+use crate::{Client, ServerEvent, ServerState};
+
+/// Prometheus metrics of a server, kept up to date by [`watch`](Self::watch),
+/// and of the requests of every client returned by [`Client::with_metrics`].
+///
+/// | Metric                                     | Kind      | Notes                                                      |
+/// |--------------------------------------------|-----------|------------------------------------------------------------|
+/// | `minecraft_players_online`                 | gauge     | From the status, polled or notified                        |
+/// | `minecraft_max_players`                    | gauge     |                                                            |
+/// | `minecraft_view_distance`                  | gauge     |                                                            |
+/// | `minecraft_started`                        | gauge     | `1` while the server is running                            |
+/// | `minecraft_joins_total`                    | counter   |                                                            |
+/// | `minecraft_leaves_total`                   | counter   |                                                            |
+/// | `minecraft_bans_total`                     | counter   |                                                            |
+/// | `minecraft_ip_bans_total`                  | counter   |                                                            |
+/// | `minecraft_saves_total`                    | counter   |                                                            |
+/// | `mc_rpc_request_duration_seconds{method}`  | histogram | Waiting for earlier requests on the connection included    |
+/// | `mc_rpc_request_errors_total{method}`      | counter   |                                                            |
+/// | `mc_rpc_kicks_total`                       | counter   | Players kicked through a client with these metrics         |
+/// | `mc_rpc_reconnects_total`                  | counter   |                                                            |
+///
+/// Cloning is cheap, every clone updates the same metrics.
+///
+/// ## Example
+/// ```no_run
+/// # use std::time::Duration;
+/// # use mc_rpc::metrics::Metrics;
+/// # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {
+/// let metrics = Metrics::new();
+/// let client = client.with_metrics(&metrics);
+///
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:9100").await?;
+/// tokio::spawn(axum::serve(listener, metrics.router()).into_future());
+/// metrics.watch(&client, Duration::from_secs(15)).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    registry: Registry,
+    players_online: IntGauge,
+    max_players: IntGauge,
+    view_distance: IntGauge,
+    started: IntGauge,
+    joins: IntCounter,
+    leaves: IntCounter,
+    kicks: IntCounter,
+    bans: IntCounter,
+    ip_bans: IntCounter,
+    saves: IntCounter,
+    request_duration: HistogramVec,
+    request_errors: IntCounterVec,
+    reconnects: IntCounter,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Creates every metric in a registry of its own, see [`registry`](Self::registry).
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        fn register<T: Collector + Clone + 'static>(registry: &Registry, metric: T) -> T {
+            registry
+                .register(Box::new(metric.clone()))
+                .expect("every metric has a name of its own");
+            metric
+        }
+        let gauge = |name: &str, help: &str| {
+            register(&registry, IntGauge::new(name, help).expect("valid metric"))
+        };
+        let counter = |name: &str, help: &str| {
+            register(
+                &registry,
+                IntCounter::new(name, help).expect("valid metric"),
+            )
+        };
+
+        Self {
+            players_online: gauge("minecraft_players_online", "Players online"),
+            max_players: gauge("minecraft_max_players", "Maximum players allowed online"),
+            view_distance: gauge("minecraft_view_distance", "View distance in chunks"),
+            started: gauge("minecraft_started", "Whether the server is running"),
+            joins: counter("minecraft_joins_total", "Players that joined"),
+            leaves: counter("minecraft_leaves_total", "Players that left"),
+            bans: counter("minecraft_bans_total", "Players added to the ban list"),
+            ip_bans: counter("minecraft_ip_bans_total", "Ips added to the ip ban list"),
+            saves: counter("minecraft_saves_total", "Completed saves"),
+            request_duration: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new("mc_rpc_request_duration_seconds", "Request latency"),
+                    &["method"],
+                )
+                .expect("valid metric"),
+            ),
+            request_errors: register(
+                &registry,
+                IntCounterVec::new(
+                    Opts::new("mc_rpc_request_errors_total", "Requests that failed"),
+                    &["method"],
+                )
+                .expect("valid metric"),
+            ),
+            kicks: counter("mc_rpc_kicks_total", "Players kicked through this client"),
+            reconnects: counter(
+                "mc_rpc_reconnects_total",
+                "Reconnects of the watched client",
+            ),
+            registry,
+        }
+    }
+
+    /// The registry of every metric, to add metrics of your own to.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("writing to a vec can't fail");
+        String::from_utf8(buffer).expect("the text format is UTF-8")
+    }
+
+    /// Returns an [`axum::Router`] answering `GET /metrics` with [`render`](Self::render).
+    pub fn router(&self) -> axum::Router {
+        let metrics = self.clone();
+        axum::Router::new().route(
+            "/metrics",
+            get(|| async move {
+                (
+                    [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+                    metrics.render(),
+                )
+            }),
+        )
+    }
+
+    /// Polls the server status and settings every `interval`, and updates the metrics from its notifications,
+    /// until the notification stream of `client` ends.
+    ///
+    /// Only fails if the notifications couldn't be subscribed to, a failed poll is tried again at the next one.
+    pub async fn watch(&self, client: &Client, interval: Duration) -> Result<()> {
+        let mut events = client.events().await?;
+        let mut reconnects = client.on_reconnect();
+        let mut poll = tokio::time::interval(interval);
+
+        loop {
+            tokio::select! {
+                _ = poll.tick() => self.poll(client).await,
+                event = events.next() => match event {
+                    Some(Ok(event)) => self.event(&event),
+                    // lagged behind, polling right away catches up with the gauges
+                    Some(Err(_)) => self.poll(client).await,
+                    None => return Ok(()),
+                },
+                Some(Ok(_)) = reconnects.next() => self.reconnects.inc(),
+            }
+        }
+    }
+
+    async fn poll(&self, client: &Client) {
+        if let Ok(status) = client.server().status().await {
+            self.status(&status);
+        }
+        if let Ok(max_players) = client.settings().max_players().get().await {
+            self.max_players.set(max_players.into());
+        }
+        if let Ok(view_distance) = client.settings().view_distance().get().await {
+            self.view_distance.set(view_distance.into());
+        }
+    }
+
+    fn status(&self, status: &ServerState) {
+        self.started.set(status.started.into());
+        self.players_online
+            .set(status.players.as_ref().map_or(0, Vec::len) as i64);
+    }
+
+    fn event(&self, event: &ServerEvent) {
+        match event {
+            ServerEvent::Started => self.started.set(1),
+            ServerEvent::Stopping => self.started.set(0),
+            ServerEvent::Saved => self.saves.inc(),
+            // the players online only come from the status, counting them here drifts whenever an event is missed
+            ServerEvent::PlayerJoined(_) => self.joins.inc(),
+            ServerEvent::PlayerLeft(_) => self.leaves.inc(),
+            ServerEvent::BanAdded(_) => self.bans.inc(),
+            ServerEvent::IpBanAdded(_) => self.ip_bans.inc(),
+            ServerEvent::Status(status) => self.status(status),
+            _ => {}
+        }
+    }
+
+    /// Records a request of a client with these metrics, with its raw result.
+    pub(crate) fn observe_request(&self, method: &str, start: Instant, result: &Result<Value>) {
+        self.request_duration
+            .with_label_values(&[method])
+            .observe(start.elapsed().as_secs_f64());
+
+        match result {
+            // the server doesn't notify about kicks, but answers with the players it kicked
+            Ok(Value::Array(kicked)) if method == "minecraft:players/kick" => {
+                self.kicks.inc_by(kicked.len() as u64);
+            }
+            Ok(_) => {}
+            Err(_) => self.request_errors.with_label_values(&[method]).inc(),
+        }
+    }
+}
+
+impl Client {
+    /// Returns a clone of this client whose requests are recorded in `metrics`, see [`Metrics`].
+    ///
+    /// The clone shares the connection and notification subscriptions of this client.
+    pub fn with_metrics(&self, metrics: &Metrics) -> Self {
+        Self(
+            self.0.clone(),
+            self.1.clone(),
+            self.2.with_metrics(metrics.clone()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Player;
+
+    fn status(players: &[&str]) -> ServerEvent {
+        ServerEvent::Status(ServerState {
+            players: Some(players.iter().map(|name| Player::by_name(*name)).collect()),
+            started: true,
+            version: None,
+        })
+    }
+
+    #[test]
+    fn players_online_only_come_from_the_status() {
+        let metrics = Metrics::new();
+        metrics.event(&status(&["Alex", "Steve"]));
+        assert_eq!(metrics.players_online.get(), 2);
+
+        // a missed join doesn't leave the gauge off by one after the next leave
+        metrics.event(&ServerEvent::PlayerLeft(Player::by_name("Steve")));
+        metrics.event(&ServerEvent::PlayerLeft(Player::by_name("Herobrine")));
+        assert_eq!(metrics.players_online.get(), 2);
+        assert_eq!(metrics.leaves.get(), 2);
+
+        metrics.event(&status(&["Alex"]));
+        assert_eq!(metrics.players_online.get(), 1);
+    }
+
+    #[test]
+    fn kicks_are_client_metrics() {
+        let metrics = Metrics::new();
+        let kicked = Ok(serde_json::json!([{ "name": "Steve" }]));
+        metrics.observe_request("minecraft:players/kick", Instant::now(), &kicked);
+
+        assert_eq!(metrics.kicks.get(), 1);
+        assert!(metrics.render().contains("mc_rpc_kicks_total 1"));
+        assert!(!metrics.render().contains("minecraft_kicks_total"));
+    }
+}
@@ -0,0 +1,599 @@
+//! An in-process Minecraft Server Management Protocol server, implementing the latest protocol version, for testing code built on [`Client`](crate::Client).
+//!
+//! The underlying client only starts listening for a response after its request is sent,
+//! which a server on the same machine can occasionally beat on a multi-threaded runtime.
+//! Tests should use the default current-thread runtime of `#[tokio::test]`.
+//!
+//! ## Example
+//! ```no_run
+//! use mc_rpc::{Client, ClientConfig, Player, ServerEvent, StreamExt, mock::MockServer};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let server = MockServer::start().await?;
+//! let client = Client::new(server.url(), ClientConfig::default()).await?;
+//!
+//! let mut events = client.events().await?;
+//! server.join(Player::by_name("Steve"));
+//!
+//! assert!(matches!(events.next().await, Some(Ok(ServerEvent::PlayerJoined(_)))));
+//! assert_eq!(client.players().get().await?.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    sync::{Mutex, MutexGuard},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::{
+    Difficulty, Dispatcher, GameType, HandlerError, IncomingIpBan, IpBan, KickPlayer,
+    ManagementHandler, Notifier, Operator, Player, ServerEvent, ServerState, SystemMessage,
+    TypedGameRule, TypedGameRuleType, TypedGameRuleValue, UntypedGameRule, UntypedGameRuleValue,
+    UserBan, Version, gamerule::GameRules, serve,
+};
+
+/// Everything the [`MockServer`] keeps in memory, served and modified by the protocol methods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockState {
+    pub allowlist: Vec<Player>,
+    pub bans: Vec<UserBan>,
+    pub ip_bans: Vec<IpBan>,
+    pub operators: Vec<Operator>,
+    /// The players currently online.
+    pub players: Vec<Player>,
+    /// Every `minecraft:serversettings/*` value, keyed by the setting name, e.g. `max_players`.
+    pub settings: BTreeMap<String, Value>,
+    pub gamerules: Vec<TypedGameRule>,
+    pub started: bool,
+    pub version: Version,
+}
+
+impl Default for MockState {
+    /// A started server with vanilla settings and nobody online.
+    fn default() -> Self {
+        let settings = [
+            ("autosave", json!(true)),
+            ("difficulty", json!(Difficulty::Easy)),
+            ("enforce_allowlist", json!(false)),
+            ("use_allowlist", json!(false)),
+            ("max_players", json!(20)),
+            ("pause_when_empty_seconds", json!(60)),
+            ("player_idle_timeout", json!(0)),
+            ("allow_flight", json!(false)),
+            ("motd", json!("A Minecraft Server")),
+            ("spawn_protection_radius", json!(16)),
+            ("force_game_mode", json!(false)),
+            ("game_mode", json!(GameType::Survival)),
+            ("view_distance", json!(10)),
+            ("simulation_distance", json!(10)),
+            ("accept_transfers", json!(false)),
+            ("status_heartbeat_interval", json!(0)),
+            ("operator_user_permission_level", json!(4)),
+            ("hide_online_players", json!(false)),
+            ("status_replies", json!(true)),
+            ("entity_broadcast_range", json!(100)),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+        Self {
+            allowlist: vec![],
+            bans: vec![],
+            ip_bans: vec![],
+            operators: vec![],
+            players: vec![],
+            settings,
+            gamerules: GameRules::vanilla().to_rules(),
+            started: true,
+            version: Version {
+                name: "25w44a".to_string(),
+                // not meaningful for the mock, nothing in the protocol checks it
+                protocol: 0,
+            },
+        }
+    }
+}
+
+/// A local WebSocket JSON-RPC server implementing every method in the schema against a [`MockState`].
+///
+/// The server shuts down when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    dispatcher: Dispatcher<MockHandler>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts a server with the [default](MockState::default) state on a random local port.
+    pub async fn start() -> std::io::Result<Self> {
+        Self::start_with_state(MockState::default()).await
+    }
+
+    /// Starts a server with the given `state` on a random local port.
+    pub async fn start_with_state(state: MockState) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let notifier = Notifier::default();
+        let dispatcher = Dispatcher::new(MockHandler {
+            state: Mutex::new(state),
+            notifier: notifier.clone(),
+        });
+
+        let handle = tokio::spawn({
+            let dispatcher = dispatcher.clone();
+            async move {
+                let _ = serve(listener, dispatcher, notifier).await;
+            }
+        });
+
+        Ok(Self {
+            addr,
+            dispatcher,
+            handle,
+        })
+    }
+
+    /// The `ws://` url to pass to [`Client::new`](crate::Client::new).
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// The address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns a snapshot of the current state.
+    pub fn state(&self) -> MockState {
+        self.handler().lock().clone()
+    }
+
+    /// Modifies the state in place, without sending any notifications.
+    pub fn update_state<R>(&self, f: impl FnOnce(&mut MockState) -> R) -> R {
+        f(&mut self.handler().lock())
+    }
+
+    /// Sends `event` to every connected client.
+    pub fn notify(&self, event: ServerEvent) {
+        self.handler().notifier.notify(&event);
+    }
+
+    /// Puts `player` online and sends [`ServerEvent::PlayerJoined`].
+    pub fn join(&self, player: Player) {
+        self.handler().mutate(|state, events| {
+            state.players.push(player.clone());
+            events.push(ServerEvent::PlayerJoined(player));
+        });
+    }
+
+    /// Takes `player` offline and sends [`ServerEvent::PlayerLeft`].
+    pub fn leave(&self, player: Player) {
+        self.handler().mutate(|state, events| {
+            state.players.retain(|p| !p.matches(&player));
+            events.push(ServerEvent::PlayerLeft(player));
+        });
+    }
+
+    /// Sends [`ServerEvent::Saving`] followed by [`ServerEvent::Saved`], like a real save would.
+    pub fn save(&self) {
+        self.notify(ServerEvent::Saving);
+        self.notify(ServerEvent::Saved);
+    }
+
+    fn handler(&self) -> &MockHandler {
+        self.dispatcher.handler()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[derive(Debug)]
+struct MockHandler {
+    state: Mutex<MockState>,
+    notifier: Notifier,
+}
+
+impl MockHandler {
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs `f` on the state, then sends every event it pushed, like the real server does once a change is made.
+    fn mutate<R>(&self, f: impl FnOnce(&mut MockState, &mut Vec<ServerEvent>) -> R) -> R {
+        let mut events = vec![];
+        let result = f(&mut self.lock(), &mut events);
+        for event in &events {
+            self.notifier.notify(event);
+        }
+        result
+    }
+
+    fn setting<T: DeserializeOwned>(&self, name: &str) -> Result<T, HandlerError> {
+        let value = self.lock().settings.get(name).cloned();
+        let Some(value) = value else {
+            return Err(HandlerError::internal(format!(
+                "The mock state has no setting '{name}'"
+            )));
+        };
+        serde_json::from_value(value).map_err(|e| HandlerError::internal(e.to_string()))
+    }
+
+    fn set_setting<T: Serialize>(&self, name: &str, value: T) -> Result<T, HandlerError> {
+        let json =
+            serde_json::to_value(&value).map_err(|e| HandlerError::internal(e.to_string()))?;
+        self.lock().settings.insert(name.to_string(), json);
+        Ok(value)
+    }
+}
+
+/// Implements the getter and setter of every `minecraft:serversettings/*` setting on top of [`MockState::settings`].
+macro_rules! settings {
+    ($($get:ident, $set:ident => $name:literal: $type:ty;)*) => {$(
+        async fn $get(&self) -> Result<$type, HandlerError> {
+            self.setting($name)
+        }
+
+        async fn $set(&self, value: $type) -> Result<$type, HandlerError> {
+            self.set_setting($name, value)
+        }
+    )*};
+}
+
+impl ManagementHandler for MockHandler {
+    async fn allowlist(&self) -> Result<Vec<Player>, HandlerError> {
+        Ok(self.lock().allowlist.clone())
+    }
+
+    async fn allowlist_set(&self, players: Vec<Player>) -> Result<Vec<Player>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            let (added, removed) = diff(&state.allowlist, &players, Player::matches);
+            events.extend(removed.into_iter().map(ServerEvent::AllowlistRemoved));
+            events.extend(added.into_iter().map(ServerEvent::AllowlistAdded));
+            state.allowlist = players;
+            state.allowlist.clone()
+        }))
+    }
+
+    async fn allowlist_add(&self, add: Vec<Player>) -> Result<Vec<Player>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            for player in add {
+                if !state.allowlist.iter().any(|p| p.matches(&player)) {
+                    state.allowlist.push(player.clone());
+                    events.push(ServerEvent::AllowlistAdded(player));
+                }
+            }
+            state.allowlist.clone()
+        }))
+    }
+
+    async fn allowlist_remove(&self, remove: Vec<Player>) -> Result<Vec<Player>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            for player in remove {
+                if let Some(i) = state.allowlist.iter().position(|p| p.matches(&player)) {
+                    events.push(ServerEvent::AllowlistRemoved(state.allowlist.remove(i)));
+                }
+            }
+            state.allowlist.clone()
+        }))
+    }
+
+    async fn allowlist_clear(&self) -> Result<Vec<Player>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            events.extend(state.allowlist.drain(..).map(ServerEvent::AllowlistRemoved));
+            vec![]
+        }))
+    }
+
+    async fn bans(&self) -> Result<Vec<UserBan>, HandlerError> {
+        Ok(self.lock().bans.clone())
+    }
+
+    async fn bans_set(&self, bans: Vec<UserBan>) -> Result<Vec<UserBan>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            let (added, removed) = diff(&state.bans, &bans, |a, b| a.player.matches(&b.player));
+            events.extend(
+                removed
+                    .into_iter()
+                    .map(|b| ServerEvent::BanRemoved(b.player)),
+            );
+            events.extend(added.into_iter().map(ServerEvent::BanAdded));
+            state.bans = bans;
+            state.bans.clone()
+        }))
+    }
+
+    async fn bans_add(&self, add: Vec<UserBan>) -> Result<Vec<UserBan>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            for ban in add {
+                state.bans.retain(|b| !b.player.matches(&ban.player));
+                state.bans.push(ban.clone());
+                events.push(ServerEvent::BanAdded(ban));
+            }
+            state.bans.clone()
+        }))
+    }
+
+    async fn bans_remove(&self, remove: Vec<Player>) -> Result<Vec<UserBan>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            for player in remove {
+                if let Some(i) = state.bans.iter().position(|b| b.player.matches(&player)) {
+                    events.push(ServerEvent::BanRemoved(state.bans.remove(i).player));
+                }
+            }
+            state.bans.clone()
+        }))
+    }
+
+    async fn bans_clear(&self) -> Result<Vec<UserBan>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            events.extend(
+                state
+                    .bans
+                    .drain(..)
+                    .map(|b| ServerEvent::BanRemoved(b.player)),
+            );
+            vec![]
+        }))
+    }
+
+    async fn ip_bans(&self) -> Result<Vec<IpBan>, HandlerError> {
+        Ok(self.lock().ip_bans.clone())
+    }
+
+    async fn ip_bans_set(&self, banlist: Vec<IpBan>) -> Result<Vec<IpBan>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            let (added, removed) = diff(&state.ip_bans, &banlist, |a, b| a.ip == b.ip);
+            events.extend(removed.into_iter().map(|b| ServerEvent::IpBanRemoved(b.ip)));
+            events.extend(added.into_iter().map(ServerEvent::IpBanAdded));
+            state.ip_bans = banlist;
+            state.ip_bans.clone()
+        }))
+    }
+
+    async fn ip_bans_add(&self, add: Vec<IncomingIpBan>) -> Result<Vec<IpBan>, HandlerError> {
+        let bans = add
+            .into_iter()
+            .map(ip_ban)
+            .collect::<Result<Vec<IpBan>, HandlerError>>()?;
+        Ok(self.mutate(|state, events| {
+            for ban in bans {
+                state.ip_bans.retain(|b| b.ip != ban.ip);
+                state.ip_bans.push(ban.clone());
+                events.push(ServerEvent::IpBanAdded(ban));
+            }
+            state.ip_bans.clone()
+        }))
+    }
+
+    async fn ip_bans_remove(&self, ip: Vec<String>) -> Result<Vec<IpBan>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            for ip in ip {
+                if let Some(i) = state.ip_bans.iter().position(|b| b.ip == ip) {
+                    events.push(ServerEvent::IpBanRemoved(state.ip_bans.remove(i).ip));
+                }
+            }
+            state.ip_bans.clone()
+        }))
+    }
+
+    async fn ip_bans_clear(&self) -> Result<Vec<IpBan>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            events.extend(
+                state
+                    .ip_bans
+                    .drain(..)
+                    .map(|b| ServerEvent::IpBanRemoved(b.ip)),
+            );
+            vec![]
+        }))
+    }
+
+    async fn players(&self) -> Result<Vec<Player>, HandlerError> {
+        Ok(self.lock().players.clone())
+    }
+
+    async fn players_kick(&self, kick: Vec<KickPlayer>) -> Result<Vec<Player>, HandlerError> {
+        for message in kick.iter().filter_map(|k| k.message.as_ref()) {
+            message
+                .validate()
+                .map_err(|e| HandlerError::invalid_params(e.to_string()))?;
+        }
+        Ok(self.mutate(|state, events| {
+            let mut kicked = vec![];
+            for kick in kick {
+                if let Some(i) = state.players.iter().position(|p| p.matches(&kick.player)) {
+                    let player = state.players.remove(i);
+                    kicked.push(player.clone());
+                    events.push(ServerEvent::PlayerLeft(player));
+                }
+            }
+            kicked
+        }))
+    }
+
+    async fn operators(&self) -> Result<Vec<Operator>, HandlerError> {
+        Ok(self.lock().operators.clone())
+    }
+
+    async fn operators_set(&self, operators: Vec<Operator>) -> Result<Vec<Operator>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            let (added, removed) = diff(&state.operators, &operators, |a, b| {
+                a.player.matches(&b.player)
+            });
+            events.extend(removed.into_iter().map(ServerEvent::OperatorRemoved));
+            events.extend(added.into_iter().map(ServerEvent::OperatorAdded));
+            state.operators = operators;
+            state.operators.clone()
+        }))
+    }
+
+    async fn operators_add(&self, add: Vec<Operator>) -> Result<Vec<Operator>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            for operator in add {
+                state
+                    .operators
+                    .retain(|o| !o.player.matches(&operator.player));
+                state.operators.push(operator.clone());
+                events.push(ServerEvent::OperatorAdded(operator));
+            }
+            state.operators.clone()
+        }))
+    }
+
+    async fn operators_remove(&self, remove: Vec<Player>) -> Result<Vec<Operator>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            for player in remove {
+                if let Some(i) = state
+                    .operators
+                    .iter()
+                    .position(|o| o.player.matches(&player))
+                {
+                    events.push(ServerEvent::OperatorRemoved(state.operators.remove(i)));
+                }
+            }
+            state.operators.clone()
+        }))
+    }
+
+    async fn operators_clear(&self) -> Result<Vec<Operator>, HandlerError> {
+        Ok(self.mutate(|state, events| {
+            events.extend(state.operators.drain(..).map(ServerEvent::OperatorRemoved));
+            vec![]
+        }))
+    }
+
+    async fn server_status(&self) -> Result<ServerState, HandlerError> {
+        let state = self.lock();
+        Ok(ServerState {
+            players: Some(state.players.clone()),
+            started: state.started,
+            version: Some(state.version.clone()),
+        })
+    }
+
+    async fn server_save(&self, _flush: bool) -> Result<bool, HandlerError> {
+        self.mutate(|_, events| events.extend([ServerEvent::Saving, ServerEvent::Saved]));
+        Ok(true)
+    }
+
+    async fn server_stop(&self) -> Result<bool, HandlerError> {
+        self.mutate(|state, events| {
+            state.started = false;
+            events.push(ServerEvent::Stopping);
+        });
+        Ok(true)
+    }
+
+    async fn server_system_message(&self, message: SystemMessage) -> Result<bool, HandlerError> {
+        message
+            .message
+            .validate()
+            .map_err(|e| HandlerError::invalid_params(e.to_string()))?;
+        Ok(true)
+    }
+
+    settings! {
+        settings_autosave, settings_autosave_set => "autosave": bool;
+        settings_difficulty, settings_difficulty_set => "difficulty": Difficulty;
+        settings_enforce_allowlist, settings_enforce_allowlist_set => "enforce_allowlist": bool;
+        settings_use_allowlist, settings_use_allowlist_set => "use_allowlist": bool;
+        settings_max_players, settings_max_players_set => "max_players": i32;
+        settings_pause_when_empty_seconds, settings_pause_when_empty_seconds_set => "pause_when_empty_seconds": i32;
+        settings_player_idle_timeout, settings_player_idle_timeout_set => "player_idle_timeout": i32;
+        settings_allow_flight, settings_allow_flight_set => "allow_flight": bool;
+        settings_motd, settings_motd_set => "motd": String;
+        settings_spawn_protection_radius, settings_spawn_protection_radius_set => "spawn_protection_radius": i32;
+        settings_force_game_mode, settings_force_game_mode_set => "force_game_mode": bool;
+        settings_game_mode, settings_game_mode_set => "game_mode": GameType;
+        settings_view_distance, settings_view_distance_set => "view_distance": i32;
+        settings_simulation_distance, settings_simulation_distance_set => "simulation_distance": i32;
+        settings_accept_transfers, settings_accept_transfers_set => "accept_transfers": bool;
+        settings_status_heartbeat_interval, settings_status_heartbeat_interval_set => "status_heartbeat_interval": i32;
+        settings_operator_user_permission_level, settings_operator_user_permission_level_set => "operator_user_permission_level": i32;
+        settings_hide_online_players, settings_hide_online_players_set => "hide_online_players": bool;
+        settings_status_replies, settings_status_replies_set => "status_replies": bool;
+        settings_entity_broadcast_range, settings_entity_broadcast_range_set => "entity_broadcast_range": i32;
+    }
+
+    async fn gamerules(&self) -> Result<Vec<TypedGameRule>, HandlerError> {
+        Ok(self.lock().gamerules.clone())
+    }
+
+    async fn gamerules_update(
+        &self,
+        gamerule: UntypedGameRule,
+    ) -> Result<TypedGameRule, HandlerError> {
+        let value = match gamerule.value {
+            UntypedGameRuleValue::Boolean(b) => TypedGameRuleValue::Boolean(b),
+            UntypedGameRuleValue::Integer(i) => TypedGameRuleValue::Integer(i),
+        };
+
+        self.mutate(|state, events| {
+            let Some(rule) = state.gamerules.iter_mut().find(|r| r.key == gamerule.key) else {
+                return Err(HandlerError::invalid_params(format!(
+                    "Unknown gamerule '{}'",
+                    gamerule.key
+                )));
+            };
+            if rule._type != rule_type(&value) {
+                return Err(HandlerError::invalid_params(format!(
+                    "Wrong value type for gamerule '{}'",
+                    gamerule.key
+                )));
+            }
+            rule.value = value;
+            events.push(ServerEvent::GameruleUpdated(rule.clone()));
+            Ok(rule.clone())
+        })
+    }
+}
+
+fn ip_ban(incoming: IncomingIpBan) -> Result<IpBan, HandlerError> {
+    // the real server can look up the ip of an online player, the mock has no ips to look up
+    let Some(ip) = incoming.ip else {
+        return Err(HandlerError::invalid_params(
+            "The mock server requires an ip for every ip ban",
+        ));
+    };
+
+    Ok(IpBan {
+        expires: incoming.expires,
+        ip,
+        reason: incoming.reason,
+        source: incoming.source,
+    })
+}
+
+fn rule_type(value: &TypedGameRuleValue) -> TypedGameRuleType {
+    match value {
+        TypedGameRuleValue::Boolean(_) => TypedGameRuleType::Boolean,
+        TypedGameRuleValue::Integer(_) => TypedGameRuleType::Integer,
+    }
+}
+
+/// Returns what `new` adds to and removes from `old`.
+fn diff<T: Clone>(old: &[T], new: &[T], same: impl Fn(&T, &T) -> bool) -> (Vec<T>, Vec<T>) {
+    let added = new
+        .iter()
+        .filter(|n| !old.iter().any(|o| same(o, n)))
+        .cloned()
+        .collect();
+    let removed = old
+        .iter()
+        .filter(|o| !new.iter().any(|n| same(o, n)))
+        .cloned()
+        .collect();
+    (added, removed)
+}
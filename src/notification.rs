@@ -0,0 +1,133 @@
+use std::{collections::HashSet, sync::Arc};
+
+use futures_util::{StreamExt, future::ready, stream::select_all};
+use pale::{PaleError, Result};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::sync::{
+    Mutex,
+    broadcast::{Receiver, Sender, channel},
+    oneshot,
+};
+use tokio_stream::{
+    Stream,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError},
+};
+
+/// The error yielded by notification streams, like [`Client::events`](crate::Client::events).
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    /// The notification params didn't match what the schema describes.
+    #[error("Failed to decode notification params: {0}")]
+    Decode(#[from] serde_json::Error),
+    /// The stream fell behind and this many notifications, of any kind, were dropped before it received them.
+    #[error("Notification stream lagged behind, {0} notifications were dropped")]
+    Lagged(u64),
+}
+
+/// A notification method and its raw `params`.
+pub(crate) type RawNotification = (Arc<str>, Value);
+
+/// A single set of subscriptions, one per notification method, shared by every notification stream of a [`Client`](crate::Client)
+/// and its `DynamicClient`s.
+///
+/// Every stream gets its own [`Receiver`] of the same broadcast, so a slow stream shows up as [`NotificationError::Lagged`]
+/// instead of silently missing notifications.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Notifications(Arc<Mutex<Subscriptions>>);
+
+#[derive(Debug, Default)]
+struct Subscriptions {
+    /// The broadcast every subscribed notification is sent to, `None` before the first subscription and after the client closed.
+    sender: Option<Sender<RawNotification>>,
+    /// Every method that is subscribed to.
+    methods: HashSet<Arc<str>>,
+}
+
+impl Notifications {
+    /// Returns a new [`Receiver`] of every subscribed notification, subscribing to the ones in `methods` that aren't yet.
+    pub(crate) async fn subscribe(
+        &self,
+        client: &pale::Client,
+        methods: &[&str],
+    ) -> Result<Receiver<RawNotification>> {
+        if !client.is_connected().await {
+            return Err(PaleError::ClientDisconnected);
+        }
+
+        let mut subscriptions = self.0.lock().await;
+        let sender = subscriptions
+            .sender
+            .get_or_insert_with(|| channel(client.config.channel_capacity).0)
+            .clone();
+        let receiver = sender.subscribe();
+
+        let new = methods
+            .iter()
+            .filter(|method| !subscriptions.methods.contains(**method))
+            .map(|method| Arc::<str>::from(*method))
+            .collect::<Vec<Arc<str>>>();
+        if !new.is_empty() {
+            self.forward(client, new.clone(), sender).await?;
+            subscriptions.methods.extend(new);
+        }
+
+        Ok(receiver)
+    }
+
+    /// Subscribes to `methods` in a task that sends their notifications to `sender` until the client is closed.
+    async fn forward(
+        &self,
+        client: &pale::Client,
+        methods: Vec<Arc<str>>,
+        sender: Sender<RawNotification>,
+    ) -> Result<()> {
+        let (subscribed_tx, subscribed_rx) = oneshot::channel();
+        let shared = self.0.clone();
+        let client = client.clone();
+        // the subscriptions borrow the client, so they have to live in the task together
+        tokio::spawn(async move {
+            let mut streams = Vec::with_capacity(methods.len());
+            for method in methods {
+                match client.subscribe::<Value>(method.to_string()).await {
+                    Ok(stream) => streams.push(stream.map(move |params| (method.clone(), params))),
+                    Err(e) => {
+                        let _ = subscribed_tx.send(Err(e));
+                        return;
+                    }
+                }
+            }
+            let _ = subscribed_tx.send(Ok(()));
+
+            let mut streams = select_all(streams);
+            while let Some((method, params)) = streams.next().await {
+                // any json is a valid `Value`, so this is only ever missing if the server sent no params
+                let params = params.and_then(|p| p.ok()).unwrap_or_default();
+                // not having any receivers right now is fine, they come and go with the streams
+                let _ = sender.send((method, params));
+            }
+
+            // the client was closed, dropping the last sender ends every stream
+            *shared.lock().await = Subscriptions::default();
+        });
+
+        subscribed_rx
+            .await
+            .unwrap_or(Err(PaleError::ClientDisconnected))
+    }
+}
+
+/// Turns a [`Receiver`] from [`Notifications::subscribe`] into a stream of whatever `decode` picks out of it.
+pub(crate) fn decode_stream<T>(
+    receiver: Receiver<RawNotification>,
+    decode: impl Fn(&str, Value) -> Option<std::result::Result<T, serde_json::Error>>,
+) -> impl Stream<Item = std::result::Result<T, NotificationError>> {
+    BroadcastStream::new(receiver).filter_map(move |notification| {
+        ready(match notification {
+            Ok((method, params)) => {
+                decode(&method, params).map(|r| r.map_err(NotificationError::from))
+            }
+            Err(BroadcastStreamRecvError::Lagged(n)) => Some(Err(NotificationError::Lagged(n))),
+        })
+    })
+}
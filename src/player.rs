@@ -0,0 +1,183 @@
+use thiserror::Error;
+
+use crate::Player;
+
+/// The id of a [`Player`], their UUID.
+#[cfg(feature = "uuid")]
+pub type PlayerId = uuid::Uuid;
+/// The id of a [`Player`], their UUID, checked to be one before a player is sent in a request.
+///
+/// Enable the `uuid` feature for a [`Uuid`](https://docs.rs/uuid) instead.
+#[cfg(not(feature = "uuid"))]
+pub type PlayerId = String;
+
+/// The error of a [`Player`] whose id isn't a UUID, see [`Player::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid player id '{0}'")]
+pub struct InvalidPlayerId(pub String);
+
+impl Player {
+    /// A player known by name only, the server looks up their id.
+    pub fn by_name(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// A player known by id only.
+    pub fn by_id(id: impl Into<PlayerId>) -> Self {
+        Self {
+            id: Some(id.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Whether both are the same player, by id or by name if either has no id.
+    pub fn matches(&self, other: &Player) -> bool {
+        match (&self.id, &other.id) {
+            (Some(a), Some(b)) => same_id(a, b),
+            _ => self.name.is_some() && self.name == other.name,
+        }
+    }
+
+    /// Checks that the id, if any, is a UUID, with or without hyphens.
+    ///
+    /// Players are checked before they are sent in a request, so an invalid one fails the request instead of reaching the server.
+    pub fn validate(&self) -> Result<(), InvalidPlayerId> {
+        #[cfg(not(feature = "uuid"))]
+        if let Some(id) = &self.id
+            && !is_uuid(id)
+        {
+            return Err(InvalidPlayerId(id.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "uuid")]
+fn same_id(a: &PlayerId, b: &PlayerId) -> bool {
+    a == b
+}
+
+/// Ids are compared like UUIDs, so case and hyphens don't matter.
+#[cfg(not(feature = "uuid"))]
+fn same_id(a: &PlayerId, b: &PlayerId) -> bool {
+    let digits = |id: &str| {
+        id.chars()
+            .filter(|c| *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>()
+    };
+    digits(a) == digits(b)
+}
+
+/// Whether `id` is 32 hex digits, either without hyphens or grouped 8-4-4-4-12.
+#[cfg(not(feature = "uuid"))]
+fn is_uuid(id: &str) -> bool {
+    let groups = id
+        .split('-')
+        .map(|group| group.len())
+        .collect::<Vec<usize>>();
+    (groups == [32] || groups == [8, 4, 4, 4, 12])
+        && id.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEVE: &str = "8667ba71-b85a-4004-af54-457a9734eed7";
+
+    #[cfg(not(feature = "uuid"))]
+    #[test]
+    fn uuids() {
+        assert!(is_uuid(STEVE));
+        assert!(is_uuid("8667BA71B85A4004AF54457A9734EED7"));
+        assert!(is_uuid("00000000-0000-0000-0000-000000000000"));
+
+        for id in [
+            "",
+            "Steve",
+            "8667ba71-b85a-4004-af54-457a9734eed",
+            "8667ba71-b85a-4004-af54-457a9734eed70",
+            "8667ba71b85a-4004-af54-457a9734eed7",
+            "8667ba71-b85a-4004-af54-457a9734eed7-",
+            "8667ba71-b85a-4004-af54-457a9734eedg",
+            "8667ba71-b85a-4004-af5-4457a9734eed7",
+            "{8667ba71-b85a-4004-af54-457a9734eed7}",
+            "8667ba71-b85a-4004-af54-457a9734eeä",
+        ] {
+            assert!(!is_uuid(id), "{id:?}");
+        }
+    }
+
+    #[cfg(not(feature = "uuid"))]
+    #[test]
+    fn invalid_ids_fail_validation() {
+        assert!(Player::by_id(STEVE).validate().is_ok());
+        assert!(Player::by_name("Steve").validate().is_ok());
+        assert_eq!(
+            Player::by_id("Steve").validate(),
+            Err(InvalidPlayerId("Steve".to_string()))
+        );
+    }
+
+    #[cfg(not(feature = "uuid"))]
+    fn id(id: &str) -> PlayerId {
+        id.to_string()
+    }
+
+    #[cfg(feature = "uuid")]
+    fn id(id: &str) -> PlayerId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn by_id_has_no_name() {
+        let player = Player::by_id(id(STEVE));
+        assert_eq!(player.id, Some(id(STEVE)));
+        assert_eq!(player.name, None);
+        assert!(player.validate().is_ok());
+    }
+
+    #[test]
+    fn matches_by_id_ignoring_case_and_hyphens() {
+        let steve = Player::by_id(id(STEVE));
+        assert!(steve.matches(&Player::by_id(id(&STEVE.to_uppercase()))));
+        assert!(steve.matches(&Player::by_id(id(&STEVE.replace('-', "")))));
+        assert!(!steve.matches(&Player::by_id(id("00000000-0000-0000-0000-000000000000"))));
+    }
+
+    #[test]
+    fn matches_by_name_without_ids() {
+        let steve = Player::by_name("Steve");
+        assert!(steve.matches(&Player::by_name("Steve")));
+        assert!(!steve.matches(&Player::by_name("Alex")));
+        // one id is enough to compare by name
+        assert!(steve.matches(&Player {
+            id: Some(id(STEVE)),
+            name: Some("Steve".to_string()),
+        }));
+        // without names there is nothing to compare
+        assert!(!Player::default().matches(&Player::default()));
+    }
+
+    #[test]
+    fn ids_win_over_names() {
+        let steve = Player {
+            id: Some(id(STEVE)),
+            name: Some("Steve".to_string()),
+        };
+        let renamed = Player {
+            id: Some(id(STEVE)),
+            name: Some("Alex".to_string()),
+        };
+        let other = Player {
+            id: Some(id("00000000-0000-0000-0000-000000000000")),
+            name: Some("Steve".to_string()),
+        };
+        assert!(steve.matches(&renamed));
+        assert!(!steve.matches(&other));
+    }
+}
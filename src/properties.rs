@@ -0,0 +1,358 @@
+use std::fmt::Write as _;
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::ServerSettings;
+
+/// The error of a `server.properties` value that doesn't fit its setting, see [`ServerSettings::from_properties`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid value '{value}' for '{key}'")]
+pub struct PropertiesError {
+    pub key: String,
+    pub value: String,
+}
+
+impl ServerSettings {
+    /// The `server.properties` key of every field that has one, by the field's serialized name.
+    ///
+    /// `autosave` isn't a property, the server always starts with it enabled.
+    pub const PROPERTIES: &[(&str, &str)] = &[
+        ("difficulty", "difficulty"),
+        ("enforce_allowlist", "enforce-whitelist"),
+        ("use_allowlist", "white-list"),
+        ("max_players", "max-players"),
+        ("pause_when_empty_seconds", "pause-when-empty-seconds"),
+        ("player_idle_timeout", "player-idle-timeout"),
+        ("allow_flight", "allow-flight"),
+        ("motd", "motd"),
+        ("spawn_protection_radius", "spawn-protection"),
+        ("force_game_mode", "force-gamemode"),
+        ("game_mode", "gamemode"),
+        ("view_distance", "view-distance"),
+        ("simulation_distance", "simulation-distance"),
+        ("accept_transfers", "accept-transfers"),
+        ("status_heartbeat_interval", "status-heartbeat-interval"),
+        ("operator_user_permission_level", "op-permission-level"),
+        ("hide_online_players", "hide-online-players"),
+        ("status_replies", "enable-status"),
+        (
+            "entity_broadcast_range",
+            "entity-broadcast-range-percentage",
+        ),
+    ];
+
+    /// Reads the settings of a `server.properties` file, properties that aren't settings are ignored.
+    ///
+    /// ## Example
+    /// ```
+    /// use mc_rpc::{Difficulty, ServerSettings};
+    ///
+    /// let settings = ServerSettings::from_properties("#Minecraft server properties\nmotd=Hello\\u00A7r\ndifficulty=hard\nlevel-seed=\n")?;
+    /// assert_eq!(settings.motd.as_deref(), Some("Hello§r"));
+    /// assert_eq!(settings.difficulty, Some(Difficulty::Hard));
+    /// # Ok::<(), mc_rpc::PropertiesError>(())
+    /// ```
+    pub fn from_properties(text: &str) -> Result<Self, PropertiesError> {
+        let mut settings = Map::new();
+        for (key, value) in parse(text) {
+            let Some((field, _)) = Self::PROPERTIES.iter().find(|(_, k)| *k == key) else {
+                continue;
+            };
+            let value = setting_value(field, &value).ok_or(PropertiesError { key, value })?;
+            settings.insert(field.to_string(), value);
+        }
+
+        Ok(serde_json::from_value(Value::Object(settings))
+            .expect("every value was checked on its own"))
+    }
+
+    /// Writes the settings that are set as a `server.properties` file.
+    ///
+    /// To keep the other properties of an existing file, see [`update_properties`](Self::update_properties).
+    pub fn to_properties(&self) -> String {
+        self.update_properties("#Minecraft server properties\n")
+    }
+
+    /// Replaces the settings that are set in the `server.properties` file `text`,
+    /// appending the ones it doesn't have yet, and keeps everything else as it is.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let properties = std::fs::read_to_string("server.properties")?;
+    /// let live = client.settings_snapshot().await?;
+    /// std::fs::write("server.properties", live.update_properties(&properties))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_properties(&self, text: &str) -> String {
+        let Ok(Value::Object(settings)) = serde_json::to_value(self) else {
+            unreachable!("settings serialize as an object");
+        };
+        let mut properties = Self::PROPERTIES
+            .iter()
+            .filter_map(|(field, key)| Some((*key, property_value(settings.get(*field)?))))
+            .collect::<Vec<(&str, String)>>();
+
+        let mut out = String::new();
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            // a value continued on the next lines is replaced as a whole
+            let mut logical = line.to_string();
+            let mut raw = vec![line];
+            while continues(&logical) {
+                let Some(next) = lines.next() else {
+                    break;
+                };
+                logical.pop();
+                logical.push_str(next.trim_start());
+                raw.push(next);
+            }
+
+            let replaced = parse_line(&logical)
+                .and_then(|(key, _)| properties.iter().position(|(k, _)| *k == key))
+                .map(|index| properties.remove(index));
+            match replaced {
+                Some((key, value)) => {
+                    let _ = writeln!(out, "{}={}", escape(key, true), escape(&value, false));
+                }
+                None => {
+                    for line in raw {
+                        let _ = writeln!(out, "{line}");
+                    }
+                }
+            }
+        }
+        for (key, value) in properties {
+            let _ = writeln!(out, "{}={}", escape(key, true), escape(&value, false));
+        }
+        out
+    }
+}
+
+/// The value of `field` for the property `value`, as whichever JSON type the field takes.
+fn setting_value(field: &str, value: &str) -> Option<Value> {
+    let trimmed = value.trim();
+    let typed = if let Ok(bool) = trimmed.parse::<bool>() {
+        Some(Value::Bool(bool))
+    } else {
+        trimmed.parse::<i64>().ok().map(Value::from)
+    };
+
+    typed
+        .into_iter()
+        .chain([Value::String(value.to_string())])
+        .find(|value| {
+            let single = Map::from_iter([(field.to_string(), value.clone())]);
+            serde_json::from_value::<ServerSettings>(Value::Object(single)).is_ok()
+        })
+}
+
+fn property_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// The key value pairs of a `.properties` file, in order.
+fn parse(text: &str) -> Vec<(String, String)> {
+    let mut properties = vec![];
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let mut logical = line.to_string();
+        while continues(&logical) {
+            let Some(next) = lines.next() else {
+                break;
+            };
+            logical.pop();
+            logical.push_str(next.trim_start());
+        }
+        properties.extend(parse_line(&logical));
+    }
+    properties
+}
+
+/// Whether a line ends in an odd number of backslashes, so the next line belongs to it, comments never continue.
+fn continues(line: &str) -> bool {
+    !line.trim_start().starts_with(['#', '!'])
+        && line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// The key and value of a whole logical line, `None` for blank lines and comments.
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim_start();
+    if line.is_empty() || line.starts_with(['#', '!']) {
+        return None;
+    }
+
+    // the key ends at the first unescaped `=`, `:` or whitespace
+    let mut escaped = false;
+    let mut key_end = line.len();
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            key_end = i;
+            break;
+        }
+    }
+
+    let (key, rest) = line.split_at(key_end);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix(['=', ':']).unwrap_or(rest).trim_start();
+    Some((unescape(key), unescape(rest)))
+}
+
+/// Resolves the escapes of a key or value, `\u` escapes are UTF-16 code units like in Java.
+fn unescape(text: &str) -> String {
+    fn push(units: &mut Vec<u16>, c: char) {
+        units.extend(c.encode_utf16(&mut [0; 2]).iter());
+    }
+
+    let mut units: Vec<u16> = Vec::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            push(&mut units, c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => push(&mut units, '\t'),
+            Some('n') => push(&mut units, '\n'),
+            Some('r') => push(&mut units, '\r'),
+            Some('f') => push(&mut units, '\u{c}'),
+            Some('u') => {
+                let hex = chars.by_ref().take(4).collect::<String>();
+                match u16::from_str_radix(&hex, 16) {
+                    Ok(unit) => units.push(unit),
+                    Err(_) => hex.chars().for_each(|c| push(&mut units, c)),
+                }
+            }
+            Some(c) => push(&mut units, c),
+            None => {}
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Escapes like Java's `Properties.store`, so non-ASCII characters survive any file encoding.
+fn escape(text: &str, key: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\u{c}' => out.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' ' if key || i == 0 => out.push_str("\\ "),
+            c if !(' '..='~').contains(&c) => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(out, "\\u{unit:04X}");
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surrogate_pairs_are_one_char() {
+        assert_eq!(unescape("\\uD83D\\uDE00"), "😀");
+        assert_eq!(escape("😀", false), "\\uD83D\\uDE00");
+    }
+
+    #[test]
+    fn escaped_keys() {
+        assert_eq!(
+            parse("key\\ with\\ space=value\n"),
+            [("key with space".to_string(), "value".to_string())]
+        );
+        assert_eq!(parse("a\\=b : c\n"), [("a=b".to_string(), "c".to_string())]);
+        assert_eq!(escape("key with space", true), "key\\ with\\ space");
+    }
+
+    #[test]
+    fn non_ascii_motds_round_trip() {
+        for motd in [
+            "§aGrüße§r",
+            " leading space",
+            "tab\tand\nnewline",
+            "a=b:c#d!e\\",
+            "日本語 😀",
+        ] {
+            assert_eq!(unescape(&escape(motd, false)), motd);
+
+            let settings = ServerSettings {
+                motd: Some(motd.to_string()),
+                ..Default::default()
+            };
+            let properties = settings.to_properties();
+            assert!(properties.is_ascii());
+            assert_eq!(
+                ServerSettings::from_properties(&properties).unwrap(),
+                settings
+            );
+        }
+    }
+
+    #[test]
+    fn continued_values_are_replaced_as_a_whole() {
+        let text = "motd=first \\\n    second \\\n    third\nmax-players=20\n";
+        assert_eq!(
+            parse(text),
+            [
+                ("motd".to_string(), "first second third".to_string()),
+                ("max-players".to_string(), "20".to_string()),
+            ]
+        );
+
+        let settings = ServerSettings {
+            motd: Some("Hello".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.update_properties(text),
+            "motd=Hello\nmax-players=20\n"
+        );
+    }
+
+    #[test]
+    fn updates_keep_everything_else() {
+        let text = "#Minecraft server properties\n#Sat Jan 01 00:00:00 UTC 2000\n! another comment \\\nlevel-seed=\nlevel-name : world\\\n  s\n\nmotd=Old\nunknown\\ key  =  kept as is \n";
+        let settings = ServerSettings {
+            motd: Some("New".into()),
+            max_players: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.update_properties(text),
+            "#Minecraft server properties\n#Sat Jan 01 00:00:00 UTC 2000\n! another comment \\\nlevel-seed=\nlevel-name : world\\\n  s\n\nmotd=New\nunknown\\ key  =  kept as is \nmax-players=10\n"
+        );
+    }
+
+    #[test]
+    fn invalid_values_fail() {
+        assert_eq!(
+            ServerSettings::from_properties("max-players=many\n"),
+            Err(PropertiesError {
+                key: "max-players".into(),
+                value: "many".into()
+            })
+        );
+    }
+}
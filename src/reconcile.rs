@@ -0,0 +1,352 @@
+use std::fmt::{self, Display};
+
+use pale::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Client, IpBan, Operator, Player, UserBan};
+
+/// An entry of one of the server's lists, the allowlist, operators, bans or ip bans.
+pub trait ListEntry: Clone + Serialize {
+    /// Whether both are about the same player or ip, no matter their other fields.
+    fn same_entry(&self, other: &Self) -> bool;
+    /// How the entry is shown in a plan.
+    fn label(&self) -> String;
+}
+
+impl ListEntry for Player {
+    fn same_entry(&self, other: &Self) -> bool {
+        self.matches(other)
+    }
+
+    fn label(&self) -> String {
+        match (&self.name, &self.id) {
+            (Some(name), _) => name.clone(),
+            (None, Some(id)) => id.to_string(),
+            (None, None) => "<unknown>".to_string(),
+        }
+    }
+}
+
+impl ListEntry for Operator {
+    fn same_entry(&self, other: &Self) -> bool {
+        self.player.matches(&other.player)
+    }
+
+    fn label(&self) -> String {
+        match self.permission_level {
+            Some(level) => format!("{} (level {level})", self.player.label()),
+            None => self.player.label(),
+        }
+    }
+}
+
+impl ListEntry for UserBan {
+    fn same_entry(&self, other: &Self) -> bool {
+        self.player.matches(&other.player)
+    }
+
+    fn label(&self) -> String {
+        ban_label(self.player.label(), &self.reason)
+    }
+}
+
+impl ListEntry for IpBan {
+    fn same_entry(&self, other: &Self) -> bool {
+        self.ip == other.ip
+    }
+
+    fn label(&self) -> String {
+        ban_label(self.ip.clone(), &self.reason)
+    }
+}
+
+fn ban_label(banned: String, reason: &Option<String>) -> String {
+    match reason {
+        Some(reason) => format!("{banned} ({reason})"),
+        None => banned,
+    }
+}
+
+/// The additions and removals that turn one list into another, see [`Client::reconcile_allowlist`] and [`Client::plan`].
+///
+/// Its [`Display`] lists every change on its own line, `+ entry` for additions and `- entry` for removals.
+///
+/// Additions are applied before removals, the server replaces the entry of a player or ip that is added again,
+/// so a changed entry is never missing from the list in between.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListPlan<T> {
+    /// New entries, and the new version of entries that changed.
+    pub add: Vec<T>,
+    /// Entries that are no longer desired.
+    pub remove: Vec<T>,
+}
+
+/// The changes to the allowlist, see [`Client::reconcile_allowlist`].
+pub type AllowlistPlan = ListPlan<Player>;
+
+impl<T> Default for ListPlan<T> {
+    fn default() -> Self {
+        Self {
+            add: vec![],
+            remove: vec![],
+        }
+    }
+}
+
+impl<T: ListEntry> ListPlan<T> {
+    /// The changes from `current` to `desired`.
+    ///
+    /// A desired entry is already there if a current entry for the [same](ListEntry::same_entry) player or ip
+    /// has every field the desired one sets, so desired entries only need to set what matters.
+    /// Player ids are compared like UUIDs, so case and hyphens don't matter.
+    /// Otherwise the desired entry is added, replacing a current entry for the same player or ip.
+    pub fn new(current: &[T], desired: &[T]) -> Self {
+        let mut add: Vec<T> = vec![];
+        for entry in desired {
+            let present = current
+                .iter()
+                .any(|c| c.same_entry(entry) && covers(c, entry));
+            // listing an entry twice doesn't add it twice
+            if !present && !add.iter().any(|a| a.same_entry(entry)) {
+                add.push(entry.clone());
+            }
+        }
+
+        let remove = current
+            .iter()
+            .filter(|c| !desired.iter().any(|d| d.same_entry(c)))
+            .cloned()
+            .collect();
+
+        Self { add, remove }
+    }
+}
+
+impl<T> ListPlan<T> {
+    /// Whether the list already is as desired.
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty()
+    }
+}
+
+impl<T: ListEntry> Display for ListPlan<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.add {
+            writeln!(f, "+ {}", entry.label())?;
+        }
+        for entry in &self.remove {
+            writeln!(f, "- {}", entry.label())?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `current` has every field `desired` sets, nested objects included.
+fn covers<T: Serialize>(current: &T, desired: &T) -> bool {
+    fn covers_value(current: &Value, desired: &Value) -> bool {
+        match (current, desired) {
+            (Value::Object(current), Value::Object(desired)) => desired
+                .iter()
+                .all(|(key, d)| current.get(key).is_some_and(|c| covers_value(c, d))),
+            (current, desired) => current == desired,
+        }
+    }
+
+    match (serde_json::to_value(current), serde_json::to_value(desired)) {
+        (Ok(mut current), Ok(mut desired)) => {
+            normalize_ids(&mut current);
+            normalize_ids(&mut desired);
+            covers_value(&current, &desired)
+        }
+        _ => false,
+    }
+}
+
+/// Rewrites every player id in `value` to lowercase digits without hyphens.
+fn normalize_ids(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    Value::String(id) if key == "id" => {
+                        *id = id
+                            .chars()
+                            .filter(|c| *c != '-')
+                            .map(|c| c.to_ascii_lowercase())
+                            .collect();
+                    }
+                    value => normalize_ids(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(normalize_ids),
+        _ => {}
+    }
+}
+
+impl Client {
+    /// Returns the changes [`reconcile_allowlist`](Self::reconcile_allowlist) would make, without making them.
+    pub async fn plan_allowlist(&self, desired: &[Player]) -> Result<AllowlistPlan> {
+        let current = self.allowlist().get().await?;
+        Ok(AllowlistPlan::new(&current, desired))
+    }
+
+    /// Makes the changes of `plan` with at most one `add` and one `remove` request, returning the resulting allowlist.
+    pub async fn apply_allowlist(&self, plan: &AllowlistPlan) -> Result<Vec<Player>> {
+        let mut allowlist = None;
+        // additions first, so a player that stays is never missing in between
+        if !plan.add.is_empty() {
+            allowlist = Some(self.allowlist().add(plan.add.clone()).await?);
+        }
+        if !plan.remove.is_empty() {
+            allowlist = Some(self.allowlist().remove(plan.remove.clone()).await?);
+        }
+
+        match allowlist {
+            Some(allowlist) => Ok(allowlist),
+            None => self.allowlist().get().await,
+        }
+    }
+
+    /// Changes the allowlist to be exactly `desired`, returning what changed.
+    ///
+    /// Unlike `allowlist().set(..)`, players already on the allowlist are left alone,
+    /// so a desired player without an id matches an allowlisted one by name.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use mc_rpc::Player;
+    /// # async fn example(client: mc_rpc::Client) -> mc_rpc::Result<()> {
+    /// let desired = [Player::by_name("Steve"), Player::by_name("Alex")];
+    ///
+    /// // a dry run
+    /// print!("{}", client.plan_allowlist(&desired).await?);
+    ///
+    /// let changes = client.reconcile_allowlist(&desired).await?;
+    /// println!("Added {} and removed {} players", changes.add.len(), changes.remove.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reconcile_allowlist(&self, desired: &[Player]) -> Result<AllowlistPlan> {
+        let current = self.allowlist().get().await?;
+        let plan = AllowlistPlan::new(&current, desired);
+        if plan.is_empty() {
+            return Ok(plan);
+        }
+
+        // what the server ended up with, not what was asked for
+        let allowlist = self.apply_allowlist(&plan).await?;
+        Ok(AllowlistPlan::new(&current, &allowlist))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlayerId;
+
+    const STEVE: &str = "8667ba71-b85a-4004-af54-457a9734eed7";
+
+    fn id(id: &str) -> PlayerId {
+        id.parse().unwrap()
+    }
+
+    fn operator(player: Player, level: i32) -> Operator {
+        Operator {
+            player,
+            permission_level: Some(level),
+            bypasses_player_limit: None,
+        }
+    }
+
+    #[test]
+    fn same_lists_need_no_changes() {
+        let list = [Player::by_name("Steve"), Player::by_name("Alex")];
+        assert!(ListPlan::new(&list, &list).is_empty());
+        assert!(ListPlan::<Player>::new(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn missing_entries_are_added_and_extra_ones_removed() {
+        let current = [Player::by_name("Steve"), Player::by_name("Herobrine")];
+        let desired = [Player::by_name("Steve"), Player::by_name("Alex")];
+
+        let plan = ListPlan::new(&current, &desired);
+        assert_eq!(plan.add, [Player::by_name("Alex")]);
+        assert_eq!(plan.remove, [Player::by_name("Herobrine")]);
+        assert_eq!(plan.to_string(), "+ Alex\n- Herobrine\n");
+    }
+
+    #[test]
+    fn desired_entries_only_need_to_set_what_matters() {
+        let current = [Player {
+            id: Some(id(STEVE)),
+            name: Some("Steve".to_string()),
+        }];
+
+        assert!(ListPlan::new(&current, &[Player::by_name("Steve")]).is_empty());
+        assert!(ListPlan::new(&current, &[Player::by_id(id(STEVE))]).is_empty());
+    }
+
+    #[test]
+    fn ids_differing_in_case_and_hyphens_are_the_same() {
+        let current = [Player {
+            id: Some(id(STEVE)),
+            name: Some("Steve".to_string()),
+        }];
+        let desired = [
+            Player::by_id(id(&STEVE.to_uppercase())),
+            Player::by_id(id(&STEVE.replace('-', ""))),
+        ];
+
+        assert!(ListPlan::new(&current, &desired).is_empty());
+    }
+
+    #[test]
+    fn changed_entries_are_only_added() {
+        let current = [
+            operator(Player::by_name("Steve"), 2),
+            operator(Player::by_name("Alex"), 4),
+        ];
+        let desired = [
+            operator(Player::by_name("Steve"), 4),
+            operator(Player::by_name("Alex"), 4),
+        ];
+
+        // adding replaces the old entry, so removing it as well would take the operator away
+        let plan = ListPlan::new(&current, &desired);
+        assert_eq!(plan.add, [operator(Player::by_name("Steve"), 4)]);
+        assert!(plan.remove.is_empty());
+    }
+
+    #[test]
+    fn entries_listed_twice_are_added_once() {
+        let desired = [
+            Player::by_name("Steve"),
+            Player::by_name("Steve"),
+            Player::by_name("Alex"),
+        ];
+
+        let plan = ListPlan::new(&[], &desired);
+        assert_eq!(
+            plan.add,
+            [Player::by_name("Steve"), Player::by_name("Alex")]
+        );
+    }
+
+    #[test]
+    fn ip_bans_are_the_same_by_ip() {
+        let current = [IpBan::permanent("192.168.0.1", "Spam")];
+
+        assert!(ListPlan::new(&current, &[IpBan::permanent("192.168.0.1", "Spam")]).is_empty());
+
+        let plan = ListPlan::new(&current, &[IpBan::permanent("192.168.0.2", "Spam")]);
+        assert_eq!(plan.add, [IpBan::permanent("192.168.0.2", "Spam")]);
+        assert_eq!(plan.remove, current);
+
+        let plan = ListPlan::new(&current, &[IpBan::permanent("192.168.0.1", "Griefing")]);
+        assert_eq!(plan.add, [IpBan::permanent("192.168.0.1", "Griefing")]);
+        assert!(plan.remove.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use pale::{ClientConfig, PaleError, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::batch::BatchConnection;
+
+/// Sends the requests of a single connection, shared by every clone of a `Client` and its `DynamicClient`s.
+///
+/// The underlying client starts listening for a response anew after every unrelated one it sees,
+/// so a response arriving while another request is still waiting can be missed entirely.
+/// Requests are therefore sent one at a time, each holding the connection only until its caller stops waiting for it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Requests {
+    lock: Arc<Mutex<()>>,
+    /// The connection batches are sent on.
+    batches: BatchConnection,
+    /// Overrides [`ClientConfig::request_timeout`] for the caller.
+    timeout: Option<Duration>,
+    /// Where the requests of the caller are recorded.
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics::Metrics>,
+}
+
+impl Requests {
+    pub(crate) fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn with_metrics(&self, metrics: crate::metrics::Metrics) -> Self {
+        Self {
+            metrics: Some(metrics),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn batches(&self) -> &BatchConnection {
+        &self.batches
+    }
+
+    /// The timeout of this caller on `client`.
+    pub(crate) fn timeout(&self, client: &pale::Client) -> Duration {
+        self.timeout.unwrap_or(client.config.request_timeout)
+    }
+
+    /// Sends a request on `client`, giving up once the timeout has passed in total, waiting for earlier requests included.
+    ///
+    /// Giving up, or dropping the returned future, stops waiting for the response and lets the next request go,
+    /// so a server that never answers holds up the connection for no longer than this caller's timeout.
+    /// A response that still arrives after that is ignored.
+    pub(crate) async fn send<T: DeserializeOwned>(
+        &self,
+        client: &pale::Client,
+        method: &str,
+        params: Option<HashMap<String, Value>>,
+    ) -> Result<T> {
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+        let timeout = self.timeout(client);
+
+        // the underlying client gives up on its own after its timeout, which can't be any shorter than this call's
+        let mut connection = client.clone();
+        if timeout > client.config.request_timeout {
+            connection.config = Arc::new(ClientConfig {
+                request_timeout: timeout,
+                ..(*client.config).clone()
+            });
+        }
+
+        let request = async {
+            let _guard = self.lock.lock().await;
+            connection.request::<Value>(method, params).await
+        };
+        let result = match tokio::time::timeout(timeout, request).await {
+            Ok(result) => result,
+            Err(_) => Err(PaleError::RequestTimeout),
+        };
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_request(method, start, &result);
+        }
+        Ok(serde_json::from_value(result?)?)
+    }
+}
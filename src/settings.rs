@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use pale::{PaleError, Result};
+use serde_json::{Map, Value};
+
+use crate::{BatchEntry, BatchError, Client, HandlerError, ServerSettings, batch::BatchRequests};
+
+/// The methods to read and write one field of [`ServerSettings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SettingEndpoints {
+    /// The serialized name of the field.
+    pub field: &'static str,
+    pub get: &'static str,
+    pub set: &'static str,
+    /// The name of the setter's only param.
+    pub param: &'static str,
+}
+
+/// A server setting that has to change, see [`ServerSettings::changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    /// The endpoints of the setting, by its field in [`ServerSettings`].
+    pub setting: SettingEndpoints,
+    /// The current value, `null` if it isn't known.
+    pub from: Value,
+    pub to: Value,
+}
+
+impl ServerSettings {
+    /// The changes from these settings to `desired`, for every field `desired` sets to a different value.
+    pub fn changes(&self, desired: &ServerSettings) -> serde_json::Result<Vec<SettingChange>> {
+        let current = to_map(self)?;
+        let desired = to_map(desired)?;
+
+        Ok(Self::ENDPOINTS
+            .iter()
+            .filter_map(|setting| {
+                let to = desired.get(setting.field)?;
+                let from = current.get(setting.field).cloned().unwrap_or_default();
+                (&from != to).then(|| SettingChange {
+                    setting: *setting,
+                    from,
+                    to: to.clone(),
+                })
+            })
+            .collect())
+    }
+}
+
+fn to_map(settings: &ServerSettings) -> serde_json::Result<Map<String, Value>> {
+    match serde_json::to_value(settings)? {
+        Value::Object(map) => Ok(map),
+        _ => unreachable!("settings serialize as an object"),
+    }
+}
+
+impl Client {
+    /// Reads every server setting in a single batch, see [`Client::batch`].
+    ///
+    /// Settings the server doesn't have, those it answers with [`HandlerError::METHOD_NOT_FOUND`], are left `None`.
+    /// Any other error fails the snapshot, as does a server that has none of the settings.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # async fn example(client: mc_rpc::Client) -> mc_rpc::Result<()> {
+    /// let before = client.settings_snapshot().await?;
+    /// client.settings().max_players().set(100).await?;
+    ///
+    /// // after the event
+    /// let changes = client.settings_restore(&before).await?;
+    /// println!("Restored {} settings", changes.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn settings_snapshot(&self) -> Result<ServerSettings> {
+        let mut batch = BatchRequests::default();
+        let entries = ServerSettings::ENDPOINTS
+            .iter()
+            .map(|setting| (setting.field, batch.push(setting.get, vec![])))
+            .collect::<Vec<(&str, BatchEntry<Value>)>>();
+        let results = batch.send(&self.0, &self.2).await?;
+
+        let mut settings = Map::new();
+        let mut not_found = None;
+        for (field, entry) in entries {
+            match results.get(entry) {
+                Ok(value) => {
+                    settings.insert(field.to_string(), value);
+                }
+                Err(BatchError::Rpc(error)) if error.code == HandlerError::METHOD_NOT_FOUND => {
+                    not_found = Some(error);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // without a single setting it's more likely the whole group is missing, or it isn't a management server at all
+        if settings.is_empty()
+            && let Some(error) = not_found
+        {
+            return Err(PaleError::RPC(error));
+        }
+        Ok(serde_json::from_value(Value::Object(settings))?)
+    }
+
+    /// Writes back every setting of `snapshot` that differs from the server's current one, returning what changed.
+    ///
+    /// Settings that are `None` in `snapshot` are left alone,
+    /// so a snapshot of one server can be restored on another, or only partly.
+    pub async fn settings_restore(&self, snapshot: &ServerSettings) -> Result<Vec<SettingChange>> {
+        let changes = self.settings_snapshot().await?.changes(snapshot)?;
+        self.write_settings(&changes).await?;
+        Ok(changes)
+    }
+
+    /// Calls the setter of every change, one at a time.
+    pub(crate) async fn write_settings(&self, changes: &[SettingChange]) -> Result<()> {
+        for change in changes {
+            let params = HashMap::from([(change.setting.param.to_string(), change.to.clone())]);
+            self.2
+                .send::<Value>(&self.0, change.setting.set, Some(params))
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use std::fmt::{self, Display};
+
+use pale::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Client, IncomingIpBan, IpBan, Operator, Player, ServerSettings, TypedGameRuleValue,
+    UntypedGameRule, UntypedGameRuleValue, UserBan,
+    gamerule::GameRules,
+    reconcile::{ListEntry, ListPlan},
+    settings::SettingChange,
+};
+
+/// The desired state of a server, see [`Client::apply`].
+///
+/// Everything left out is left alone, so a spec only needs to list what it manages.
+/// A list that is given is exactly what the server ends up with.
+///
+/// ## Example
+/// ```
+/// use mc_rpc::ServerSpec;
+///
+/// let spec: ServerSpec = serde_json::from_str(r#"{
+///     "operators": [{ "player": { "name": "Steve" }, "permissionLevel": 4 }],
+///     "ip_bans": [],
+///     "gamerules": { "keepInventory": true },
+///     "settings": { "motd": "Welcome!", "max_players": 20 }
+/// }"#)?;
+/// assert!(spec.allowlist.is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowlist: Option<Vec<Player>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operators: Option<Vec<Operator>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bans: Option<Vec<UserBan>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_bans: Option<Vec<IpBan>>,
+    /// Game rule values by key, rules that aren't set are left alone.
+    ///
+    /// Rules [`GameRules`] lists have to be of their type, others are sent as they are.
+    #[serde(skip_serializing_if = "GameRules::is_empty")]
+    pub gamerules: GameRules,
+    pub settings: ServerSettings,
+}
+
+/// A game rule that has to change, see [`ServerPlan`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameRuleChange {
+    pub key: String,
+    /// The current value, `None` if the server doesn't report the rule.
+    pub from: Option<TypedGameRuleValue>,
+    pub to: UntypedGameRuleValue,
+}
+
+/// The changes that bring a server to a [`ServerSpec`], see [`Client::plan`].
+///
+/// Its [`Display`] is a diff with a section for every part that changes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServerPlan {
+    pub allowlist: ListPlan<Player>,
+    pub operators: ListPlan<Operator>,
+    pub bans: ListPlan<UserBan>,
+    pub ip_bans: ListPlan<IpBan>,
+    pub gamerules: Vec<GameRuleChange>,
+    pub settings: Vec<SettingChange>,
+}
+
+impl ServerPlan {
+    /// Whether the server already is as specified.
+    pub fn is_empty(&self) -> bool {
+        self.allowlist.is_empty()
+            && self.operators.is_empty()
+            && self.bans.is_empty()
+            && self.ip_bans.is_empty()
+            && self.gamerules.is_empty()
+            && self.settings.is_empty()
+    }
+}
+
+impl Display for ServerPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list<T: ListEntry>(
+            f: &mut fmt::Formatter<'_>,
+            name: &str,
+            plan: &ListPlan<T>,
+        ) -> fmt::Result {
+            if plan.is_empty() {
+                return Ok(());
+            }
+            writeln!(f, "{name}:")?;
+            write!(f, "{plan}")
+        }
+
+        fn value(value: &impl Serialize) -> String {
+            serde_json::to_string(value).unwrap_or_default()
+        }
+
+        list(f, "allowlist", &self.allowlist)?;
+        list(f, "operators", &self.operators)?;
+        list(f, "bans", &self.bans)?;
+        list(f, "ip_bans", &self.ip_bans)?;
+
+        if !self.gamerules.is_empty() {
+            writeln!(f, "gamerules:")?;
+            for change in &self.gamerules {
+                let from = change
+                    .from
+                    .as_ref()
+                    .map(value)
+                    .unwrap_or_else(|| "unknown".to_string());
+                writeln!(f, "~ {}: {from} -> {}", change.key, value(&change.to))?;
+            }
+        }
+
+        if !self.settings.is_empty() {
+            writeln!(f, "settings:")?;
+            for change in &self.settings {
+                writeln!(
+                    f,
+                    "~ {}: {} -> {}",
+                    change.setting.field,
+                    value(&change.from),
+                    value(&change.to)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Returns the changes [`apply`](Self::apply) would make, without making them.
+    ///
+    /// Only the parts `spec` manages are read from the server,
+    /// the settings all at once with [`settings_snapshot`](Self::settings_snapshot).
+    pub async fn plan(&self, spec: &ServerSpec) -> Result<ServerPlan> {
+        let mut plan = ServerPlan::default();
+
+        if let Some(desired) = &spec.allowlist {
+            plan.allowlist = ListPlan::new(&self.allowlist().get().await?, desired);
+        }
+        if let Some(desired) = &spec.operators {
+            plan.operators = ListPlan::new(&self.operators().get().await?, desired);
+        }
+        if let Some(desired) = &spec.bans {
+            plan.bans = ListPlan::new(&self.bans().get().await?, desired);
+        }
+        if let Some(desired) = &spec.ip_bans {
+            plan.ip_bans = ListPlan::new(&self.ip_bans().get().await?, desired);
+        }
+
+        if !spec.gamerules.is_empty() {
+            let current = self.gamerules().get().await?;
+            for rule in spec.gamerules.to_rules() {
+                let from = current
+                    .iter()
+                    .find(|current| current.key == rule.key)
+                    .map(|current| current.value.clone());
+                if from.as_ref() != Some(&rule.value) {
+                    plan.gamerules.push(GameRuleChange {
+                        key: rule.key,
+                        from,
+                        to: rule.value.into(),
+                    });
+                }
+            }
+        }
+
+        if spec.settings != ServerSettings::default() {
+            let current = self.settings_snapshot().await?;
+            plan.settings = current.changes(&spec.settings)?;
+        }
+
+        Ok(plan)
+    }
+
+    /// Brings the server to `spec`, returning the changes that were made.
+    ///
+    /// Only what differs is changed, with at most one `remove` and one `add` request per list,
+    /// one `update` per game rule and one setter call per setting.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # async fn example(client: mc_rpc::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let spec: mc_rpc::ServerSpec = serde_json::from_str(&std::fs::read_to_string("server.json")?)?;
+    ///
+    /// // a dry run
+    /// print!("{}", client.plan(&spec).await?);
+    ///
+    /// let changes = client.apply(&spec).await?;
+    /// println!("Changed {} settings", changes.settings.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apply(&self, spec: &ServerSpec) -> Result<ServerPlan> {
+        let plan = self.plan(spec).await?;
+
+        // additions first, they replace changed entries so those are never missing in between
+        if !plan.allowlist.is_empty() {
+            self.apply_allowlist(&plan.allowlist).await?;
+        }
+
+        if !plan.operators.add.is_empty() {
+            self.operators().add(plan.operators.add.clone()).await?;
+        }
+        if !plan.operators.remove.is_empty() {
+            let players = plan.operators.remove.iter().map(|o| o.player.clone());
+            self.operators().remove(players.collect()).await?;
+        }
+
+        if !plan.bans.add.is_empty() {
+            self.bans().add(plan.bans.add.clone()).await?;
+        }
+        if !plan.bans.remove.is_empty() {
+            let players = plan.bans.remove.iter().map(|b| b.player.clone());
+            self.bans().remove(players.collect()).await?;
+        }
+
+        if !plan.ip_bans.add.is_empty() {
+            let bans = plan.ip_bans.add.iter().cloned().map(IncomingIpBan::from);
+            self.ip_bans().add(bans.collect()).await?;
+        }
+        if !plan.ip_bans.remove.is_empty() {
+            let ips = plan.ip_bans.remove.iter().map(|b| b.ip.clone());
+            self.ip_bans().remove(ips.collect()).await?;
+        }
+
+        for change in &plan.gamerules {
+            self.gamerules()
+                .update(UntypedGameRule {
+                    key: change.key.clone(),
+                    value: change.to.clone(),
+                })
+                .await?;
+        }
+
+        self.write_settings(&plan.settings).await?;
+
+        Ok(plan)
+    }
+}
+
+/// A ban of the same ip, with the same details.
+impl From<IpBan> for IncomingIpBan {
+    fn from(ban: IpBan) -> Self {
+        Self {
+            expires: ban.expires,
+            ip: Some(ban.ip),
+            player: None,
+            reason: ban.reason,
+            source: ban.source,
+        }
+    }
+}
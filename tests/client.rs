@@ -0,0 +1,702 @@
+//! Drives a `Client` against the `MockServer`.
+//!
+//! Every test runs on the current-thread runtime of `#[tokio::test]`, see the `mock` module.
+
+use std::{pin::pin, time::Duration};
+
+use mc_rpc::{
+    BatchError, Client, ClientConfig, Difficulty, Dispatcher, Fleet, HandlerError, IpBan,
+    ManagementHandler, Message, MessageHandler, Notifier, Operator, PaleError, Player, ServerEvent,
+    ServerSpec, StreamExt, UntypedGameRule, UntypedGameRuleValue, UserBan, mock::MockServer, serve,
+};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+async fn connect() -> (MockServer, Client) {
+    let server = MockServer::start().await.unwrap();
+    let client = Client::new(server.url(), ClientConfig::default())
+        .await
+        .unwrap();
+    (server, client)
+}
+
+/// The next item of `stream`, failing instead of hanging if it takes too long.
+async fn next<S: StreamExt + Unpin>(stream: &mut S) -> Option<S::Item> {
+    tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .expect("the stream timed out")
+}
+
+fn names(players: &[Player]) -> Vec<&str> {
+    players.iter().filter_map(|p| p.name.as_deref()).collect()
+}
+
+#[tokio::test]
+async fn allowlist_get_set_add_remove_clear() {
+    let (server, client) = connect().await;
+
+    assert!(client.allowlist().get().await.unwrap().is_empty());
+
+    let set = client
+        .allowlist()
+        .set(vec![Player::by_name("Steve")])
+        .await
+        .unwrap();
+    assert_eq!(names(&set), ["Steve"]);
+
+    let added = client
+        .allowlist()
+        .add(vec![Player::by_name("Alex")])
+        .await
+        .unwrap();
+    assert_eq!(names(&added), ["Steve", "Alex"]);
+
+    let removed = client
+        .allowlist()
+        .remove(vec![Player::by_name("Steve")])
+        .await
+        .unwrap();
+    assert_eq!(names(&removed), ["Alex"]);
+    assert_eq!(names(&client.allowlist().get().await.unwrap()), ["Alex"]);
+
+    assert!(client.allowlist().clear().await.unwrap().is_empty());
+    assert!(server.state().allowlist.is_empty());
+}
+
+#[tokio::test]
+async fn bans_ip_bans_and_operators() {
+    let (server, client) = connect().await;
+
+    let bans = client
+        .bans()
+        .add(vec![UserBan::permanent(
+            Player::by_name("Steve"),
+            "Griefing",
+        )])
+        .await
+        .unwrap();
+    assert_eq!(bans[0].reason.as_deref(), Some("Griefing"));
+    assert!(client.bans().clear().await.unwrap().is_empty());
+
+    let ip_bans = client
+        .ip_bans()
+        .add(vec![IpBan::permanent("10.0.0.1", "Spam").into()])
+        .await
+        .unwrap();
+    assert_eq!(ip_bans[0].ip, "10.0.0.1");
+    assert!(
+        client
+            .ip_bans()
+            .remove(vec!["10.0.0.1".to_string()])
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let operator = Operator {
+        bypasses_player_limit: None,
+        permission_level: Some(4),
+        player: Player::by_name("Alex"),
+    };
+    client
+        .operators()
+        .add(vec![operator.clone()])
+        .await
+        .unwrap();
+    assert_eq!(server.state().operators, [operator]);
+}
+
+#[tokio::test]
+async fn players_and_server() {
+    let (server, client) = connect().await;
+    server.join(Player::by_name("Steve"));
+
+    assert_eq!(names(&client.players().get().await.unwrap()), ["Steve"]);
+    let status = client.server().status().await.unwrap();
+    assert!(status.started);
+    assert_eq!(names(status.players.as_deref().unwrap()), ["Steve"]);
+
+    assert!(client.server().stop().await.unwrap());
+    assert!(!server.state().started);
+}
+
+#[tokio::test]
+async fn settings_get_and_set() {
+    let (server, client) = connect().await;
+
+    assert_eq!(client.settings().max_players().get().await.unwrap(), 20);
+    assert_eq!(client.settings().max_players().set(100).await.unwrap(), 100);
+    assert_eq!(client.settings().max_players().get().await.unwrap(), 100);
+
+    client
+        .settings()
+        .difficulty()
+        .set(Difficulty::Hard)
+        .await
+        .unwrap();
+    assert_eq!(
+        server.state().settings["difficulty"],
+        serde_json::json!("hard")
+    );
+}
+
+#[tokio::test]
+async fn gamerules_update() {
+    let (_server, client) = connect().await;
+
+    let rule = client
+        .gamerules()
+        .update(UntypedGameRule {
+            key: "keepInventory".to_string(),
+            value: UntypedGameRuleValue::Boolean(true),
+        })
+        .await
+        .unwrap();
+    assert_eq!(rule.key, "keepInventory");
+
+    let rules = client.gamerules().get().await.unwrap();
+    let keep_inventory = rules.iter().find(|r| r.key == "keepInventory").unwrap();
+    assert_eq!(keep_inventory.value, rule.value);
+}
+
+#[tokio::test]
+async fn events_receive_notifications() {
+    let (server, client) = connect().await;
+    let mut events = pin!(client.events().await.unwrap());
+
+    server.notify(ServerEvent::Saved);
+    server.join(Player::by_name("Steve"));
+
+    assert!(matches!(
+        next(&mut events).await,
+        Some(Ok(ServerEvent::Saved))
+    ));
+    match next(&mut events).await {
+        Some(Ok(ServerEvent::PlayerJoined(player))) => {
+            assert_eq!(player.name.as_deref(), Some("Steve"));
+        }
+        other => panic!("expected a join, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn fleet_events_leave_out_servers_that_failed_to_subscribe() {
+    let (server, client) = connect().await;
+    let (_closed_server, closed) = connect().await;
+    closed.close().await.unwrap();
+    closed
+        .wait_for_connection(false, Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut fleet = Fleet::new();
+    fleet.insert("lobby", client, ["lobby"]);
+    fleet.insert("closed", closed, ["lobby"]);
+    let (events, errors) = fleet.events().await;
+    let mut events = pin!(events);
+
+    assert_eq!(errors.keys().collect::<Vec<_>>(), ["closed"]);
+    server.notify(ServerEvent::Saved);
+    assert!(matches!(
+        next(&mut events).await,
+        Some((name, Ok(ServerEvent::Saved))) if name == "lobby"
+    ));
+}
+
+#[tokio::test]
+async fn rpc_errors_keep_their_code() {
+    let (_server, client) = connect().await;
+
+    let result = client
+        .gamerules()
+        .update(UntypedGameRule {
+            key: "notARule".to_string(),
+            value: UntypedGameRuleValue::Integer(1),
+        })
+        .await;
+    match result {
+        Err(PaleError::RPC(error)) => assert_eq!(error.code, HandlerError::INVALID_PARAMS),
+        other => panic!("expected an RPC error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn dynamic_subscriptions_share_notifications() {
+    let (server, client) = connect().await;
+    let dynamic = client.dynamic(serde_json::from_str(mc_rpc::SCHEMA).unwrap());
+
+    let mut joined = pin!(
+        dynamic
+            .subscribe("minecraft:notification/players/joined")
+            .await
+            .unwrap()
+    );
+    let mut events = pin!(client.events().await.unwrap());
+    server.join(Player::by_name("Steve"));
+
+    let params = next(&mut joined).await.unwrap().unwrap();
+    assert_eq!(params[0]["name"], "Steve");
+    assert!(matches!(
+        next(&mut events).await,
+        Some(Ok(ServerEvent::PlayerJoined(_)))
+    ));
+}
+
+/// Answers every request with `true`, except `minecraft:server/stop`, which it never answers.
+#[derive(Clone)]
+struct NeverStops;
+
+impl MessageHandler for NeverStops {
+    async fn handle(&self, message: &str) -> Option<String> {
+        let request: serde_json::Value = serde_json::from_str(message).ok()?;
+        if request["method"] == "minecraft:server/stop" {
+            return None;
+        }
+        Some(json!({ "jsonrpc": "2.0", "id": request["id"], "result": true }).to_string())
+    }
+}
+
+/// Connects to a [`NeverStops`] server with a connection wide timeout far longer than any test should take.
+async fn connect_never_stops() -> (tokio::task::JoinHandle<()>, Client) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let _ = serve(listener, NeverStops, Notifier::default()).await;
+    });
+
+    let config = ClientConfig {
+        request_timeout: Duration::from_secs(60),
+        ..ClientConfig::default()
+    };
+    (server, Client::new(url, config).await.unwrap())
+}
+
+#[tokio::test]
+async fn timed_out_requests_dont_block_the_connection() {
+    let (_server, client) = connect_never_stops().await;
+
+    let hung = client
+        .with_timeout(Duration::from_millis(200))
+        .server()
+        .stop()
+        .await;
+    assert!(matches!(hung, Err(PaleError::RequestTimeout)));
+
+    let saved = tokio::time::timeout(Duration::from_secs(5), client.server().save(true))
+        .await
+        .expect("the request after the timed out one was blocked");
+    assert!(saved.unwrap());
+}
+
+#[tokio::test]
+async fn dropped_requests_dont_block_the_connection() {
+    let (_server, client) = connect_never_stops().await;
+
+    // waits for the connection wide minute, unless it is dropped
+    let hung = tokio::spawn({
+        let client = client.clone();
+        async move { client.server().stop().await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // queued behind the hung request, gives up while waiting for it
+    let queued = client
+        .with_timeout(Duration::from_millis(100))
+        .server()
+        .save(true)
+        .await;
+    assert!(matches!(queued, Err(PaleError::RequestTimeout)));
+
+    hung.abort();
+    let saved = tokio::time::timeout(Duration::from_secs(5), client.server().save(true))
+        .await
+        .expect("the request after the dropped one was blocked");
+    assert!(saved.unwrap());
+}
+
+#[tokio::test]
+async fn batch_entries_fail_on_their_own() {
+    let (_server, client) = connect().await;
+    client
+        .allowlist()
+        .set(vec![Player::by_name("Steve")])
+        .await
+        .unwrap();
+
+    let mut batch = client.batch();
+    let allowlist = batch.allowlist();
+    let update = batch.gamerules_update(UntypedGameRule {
+        key: "notARule".to_string(),
+        value: UntypedGameRuleValue::Integer(1),
+    });
+    let results = batch.send().await.unwrap();
+
+    assert_eq!(names(&results.get(allowlist).unwrap()), ["Steve"]);
+    assert!(matches!(
+        results.get(update),
+        Err(BatchError::Rpc(error)) if error.code == HandlerError::INVALID_PARAMS
+    ));
+    assert!(!results.is_ok());
+    assert_eq!(results.errors().count(), 1);
+
+    // the connection is kept for the next batch
+    let mut batch = client.batch();
+    let players = batch.players();
+    assert!(batch.send().await.unwrap().get(players).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn batch_entries_only_fit_their_own_batch() {
+    let (_server, client) = connect().await;
+
+    let mut first = client.batch();
+    let players = first.players();
+    let mut second = client.batch();
+    second.players();
+    let results = second.send().await.unwrap();
+
+    assert!(matches!(results.get(players), Err(BatchError::OtherBatch)));
+}
+
+/// Answers every request with `true`, a batch with an array of them where `minecraft:server/stop` gets a malformed error,
+/// or rejects batches as a whole if it `rejects_batches`.
+#[derive(Clone)]
+struct Batches {
+    rejects_batches: bool,
+}
+
+impl MessageHandler for Batches {
+    async fn handle(&self, message: &str) -> Option<String> {
+        let answer = |request: &serde_json::Value| match request["method"].as_str() {
+            Some("minecraft:server/stop") => {
+                json!({ "jsonrpc": "2.0", "id": request["id"], "error": "broken" })
+            }
+            _ => json!({ "jsonrpc": "2.0", "id": request["id"], "result": true }),
+        };
+
+        let response = match serde_json::from_str(message).ok()? {
+            serde_json::Value::Array(_) if self.rejects_batches => json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": HandlerError::INVALID_REQUEST, "message": "Batches aren't supported" },
+            }),
+            serde_json::Value::Array(batch) => batch.iter().map(answer).collect(),
+            request => json!({ "jsonrpc": "2.0", "id": request["id"], "result": true }),
+        };
+        Some(response.to_string())
+    }
+}
+
+async fn connect_batches(rejects_batches: bool) -> (tokio::task::JoinHandle<()>, Client) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let _ = serve(listener, Batches { rejects_batches }, Notifier::default()).await;
+    });
+    (
+        server,
+        Client::new(url, ClientConfig::default()).await.unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn malformed_errors_only_fail_their_entry() {
+    let (_server, client) = connect_batches(false).await;
+
+    let mut batch = client.batch();
+    let saved = batch.server_save(true);
+    let stopped = batch.server_stop();
+    let results = batch.send().await.unwrap();
+
+    assert!(results.get(saved).unwrap());
+    assert!(matches!(
+        results.get(stopped),
+        Err(BatchError::Malformed(error)) if error == "broken"
+    ));
+}
+
+#[tokio::test]
+async fn rejected_batches_are_sent_one_request_at_a_time() {
+    let (_server, client) = connect_batches(true).await;
+
+    for _ in 0..2 {
+        let mut batch = client.batch();
+        let saved = batch.server_save(true);
+        let stopped = batch.server_stop();
+        let results = batch.send().await.unwrap();
+
+        assert!(results.is_ok());
+        assert!(results.get(saved).unwrap());
+        assert!(results.get(stopped).unwrap());
+    }
+}
+
+/// Only has the settings it's told to, failing to read the max players if it's `broken`.
+struct Settings {
+    motd: bool,
+    broken: bool,
+}
+
+impl ManagementHandler for Settings {
+    async fn settings_motd(&self) -> Result<String, HandlerError> {
+        match self.motd {
+            true => Ok("Hello".into()),
+            false => Err(HandlerError::method_not_found(
+                "minecraft:serversettings/motd",
+            )),
+        }
+    }
+
+    async fn settings_max_players(&self) -> Result<i32, HandlerError> {
+        match self.broken {
+            true => Err(HandlerError::internal("Can't read max players")),
+            false => Err(HandlerError::method_not_found(
+                "minecraft:serversettings/max_players",
+            )),
+        }
+    }
+}
+
+async fn connect_settings(motd: bool, broken: bool) -> (tokio::task::JoinHandle<()>, Client) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let dispatcher = Dispatcher::new(Settings { motd, broken });
+    let server = tokio::spawn(async move {
+        let _ = serve(listener, dispatcher, Notifier::default()).await;
+    });
+    (
+        server,
+        Client::new(url, ClientConfig::default()).await.unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn snapshots_leave_missing_settings_out() {
+    let (_server, client) = connect_settings(true, false).await;
+
+    let settings = client.settings_snapshot().await.unwrap();
+    assert_eq!(settings.motd.as_deref(), Some("Hello"));
+    assert_eq!(settings.max_players, None);
+
+    let (_server, client) = connect().await;
+    let settings = serde_json::to_value(client.settings_snapshot().await.unwrap()).unwrap();
+    assert_eq!(
+        settings.as_object().unwrap().len(),
+        mc_rpc::ServerSettings::ENDPOINTS.len()
+    );
+}
+
+#[tokio::test]
+async fn snapshots_fail_on_broken_settings() {
+    let (_server, client) = connect_settings(true, true).await;
+
+    assert!(matches!(
+        client.settings_snapshot().await,
+        Err(PaleError::RPC(error)) if error.code == HandlerError::INTERNAL_ERROR
+    ));
+}
+
+#[tokio::test]
+async fn snapshots_fail_without_any_settings() {
+    let (_server, client) = connect_settings(false, false).await;
+
+    assert!(matches!(
+        client.settings_snapshot().await,
+        Err(PaleError::RPC(error)) if error.code == HandlerError::METHOD_NOT_FOUND
+    ));
+}
+
+#[tokio::test]
+async fn invalid_messages_fail_before_they_are_sent() {
+    let (_server, client) = connect().await;
+    let empty = Message::default();
+
+    // only requests check messages, anything else serializes them as they are
+    assert_eq!(serde_json::to_value(&empty).unwrap(), json!({}));
+
+    let sent = client.server().system_message(empty.clone().into()).await;
+    assert!(matches!(sent, Err(PaleError::SerdeJson(_))));
+
+    let mut batch = client.batch();
+    batch.server_system_message(empty.into());
+    assert!(matches!(batch.send().await, Err(PaleError::SerdeJson(_))));
+}
+
+#[cfg(not(feature = "uuid"))]
+#[tokio::test]
+async fn invalid_player_ids_fail_before_they_are_sent() {
+    let (server, client) = connect().await;
+    let invalid = Player::by_id("Steve");
+
+    // only requests check players, anything else serializes them as they are
+    assert_eq!(
+        serde_json::to_value(&invalid).unwrap(),
+        json!({ "id": "Steve" })
+    );
+
+    let added = client.allowlist().add(vec![invalid]).await;
+    assert!(matches!(added, Err(PaleError::SerdeJson(_))));
+    assert!(server.state().allowlist.is_empty());
+}
+
+#[tokio::test]
+async fn apply_replaces_changed_operators_without_removing_them() {
+    let (server, client) = connect().await;
+    let operator = |level| Operator {
+        player: Player::by_name("Steve"),
+        permission_level: Some(level),
+        bypasses_player_limit: None,
+    };
+    client.operators().add(vec![operator(2)]).await.unwrap();
+    let mut events = pin!(client.events().await.unwrap());
+
+    let spec = ServerSpec {
+        operators: Some(vec![operator(4)]),
+        ..ServerSpec::default()
+    };
+    let plan = client.apply(&spec).await.unwrap();
+    assert_eq!(plan.operators.add, [operator(4)]);
+    assert!(plan.operators.remove.is_empty());
+
+    assert_eq!(server.state().operators, [operator(4)]);
+    match next(&mut events).await {
+        Some(Ok(ServerEvent::OperatorAdded(added))) => assert_eq!(added, operator(4)),
+        other => panic!("expected the operator to be added, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn apply_only_updates_game_rules_that_differ() {
+    let (_server, client) = connect().await;
+    let spec: ServerSpec = serde_json::from_value(
+        json!({ "gamerules": { "keepInventory": true, "randomTickSpeed": 3 } }),
+    )
+    .unwrap();
+
+    // the mock starts out with the vanilla rules, where only keepInventory differs
+    let plan = client.apply(&spec).await.unwrap();
+    let changed = plan
+        .gamerules
+        .iter()
+        .map(|change| change.key.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(changed, ["keepInventory"]);
+
+    assert!(client.plan(&spec).await.unwrap().is_empty());
+}
+
+/// Serves the gateway of a client connected to a `MockServer`, returning the gateway's address.
+#[cfg(feature = "gateway")]
+async fn serve_gateway() -> (MockServer, std::net::SocketAddr) {
+    let (server, client) = connect().await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(axum::serve(listener, client.gateway()).into_future());
+    (server, addr)
+}
+
+/// Sends a request with a JSON `body` to the gateway at `addr`, returning the status and JSON body of its response.
+#[cfg(feature = "gateway")]
+async fn http(
+    addr: std::net::SocketAddr,
+    method: &str,
+    path: &str,
+    body: Option<serde_json::Value>,
+) -> (u16, serde_json::Value) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+        .await
+        .expect("the gateway timed out")
+        .unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[cfg(feature = "gateway")]
+#[tokio::test]
+async fn gateway_forwards_requests() {
+    let (_server, addr) = serve_gateway().await;
+
+    let (status, added) = http(
+        addr,
+        "POST",
+        "/allowlist",
+        Some(json!([{ "name": "Steve" }])),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(added[0]["name"], "Steve");
+
+    let (status, allowlist) = http(addr, "GET", "/allowlist", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(allowlist, added);
+
+    let (status, motd) = http(addr, "PUT", "/settings/motd", Some(json!("Hello"))).await;
+    assert_eq!((status, motd), (200, json!("Hello")));
+    assert_eq!(
+        http(addr, "GET", "/settings/motd", None).await,
+        (200, json!("Hello"))
+    );
+}
+
+#[cfg(feature = "gateway")]
+#[tokio::test]
+async fn gateway_maps_rpc_errors_to_a_status() {
+    let (_server, addr) = serve_gateway().await;
+
+    let rule = json!({ "key": "notARule", "value": 1 });
+    let (status, error) = http(addr, "POST", "/gamerules/update", Some(rule)).await;
+    assert_eq!(status, 400);
+    assert_eq!(error["code"], HandlerError::INVALID_PARAMS);
+    assert!(error["message"].is_string());
+}
+
+#[cfg(feature = "gateway")]
+#[tokio::test]
+async fn gateway_streams_events() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (server, addr) = serve_gateway().await;
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET /events HTTP/1.1\r\nHost: {addr}\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    // the headers are only sent once the gateway subscribed
+    let mut received = String::new();
+    let mut buffer = [0; 1024];
+    let mut joined = false;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !received.contains("event: players/joined") {
+            let read = stream.read(&mut buffer).await.unwrap();
+            assert_ne!(read, 0, "the stream ended: {received}");
+            received.push_str(std::str::from_utf8(&buffer[..read]).unwrap());
+            if !joined && received.contains("\r\n\r\n") {
+                server.join(Player::by_name("Steve"));
+                joined = true;
+            }
+        }
+    })
+    .await
+    .expect("no event was streamed");
+
+    assert!(received.starts_with("HTTP/1.1 200"));
+    assert!(received.contains("text/event-stream"));
+    let data = received
+        .split("event: players/joined")
+        .nth(1)
+        .and_then(|event| event.lines().find_map(|line| line.strip_prefix("data: ")))
+        .unwrap();
+    let params: serde_json::Value = serde_json::from_str(data).unwrap();
+    assert!(params.to_string().contains("Steve"), "{params}");
+}